@@ -0,0 +1,82 @@
+use criterion::{black_box, criterion_group, criterion_main, Criterion};
+use lerp_table::{Coord, Piecewise};
+
+fn table(knots: usize) -> Piecewise {
+    let points = (0..knots)
+        .map(|i| {
+            let x = i as f64;
+            Coord::try_from((x, (x * 0.37).sin() * 10.0)).unwrap()
+        })
+        .collect::<Vec<_>>();
+    Piecewise::try_from(points).unwrap()
+}
+
+fn queries(count: usize, max_x: f64) -> Vec<f64> {
+    let mut state: u64 = 0x2545_f491_4f6c_dd1d;
+    (0..count)
+        .map(|_| {
+            state = state.wrapping_mul(6364136223846793005).wrapping_add(1);
+            (state >> 11) as f64 / (1u64 << 53) as f64 * max_x
+        })
+        .collect()
+}
+
+fn bake(c: &mut Criterion) {
+    let exact = table(200);
+    let baked = exact.bake(4096).unwrap();
+    let xs = queries(1024, 199.0);
+
+    c.bench_function("y_at_x 200 knots", |b| {
+        b.iter(|| {
+            xs.iter()
+                .map(|&x| exact.y_at_x(black_box(x)).unwrap())
+                .sum::<f64>()
+        })
+    });
+    c.bench_function("baked y_at_x 4096 samples", |b| {
+        b.iter(|| {
+            xs.iter()
+                .map(|&x| baked.y_at_x(black_box(x)).unwrap())
+                .sum::<f64>()
+        })
+    });
+}
+
+//run with and without `--features simd` to compare the batch paths
+fn batch(c: &mut Criterion) {
+    let exact = table(200);
+    let xs = queries(1 << 16, 199.0);
+
+    c.bench_function("y_at_x loop 65536 queries", |b| {
+        b.iter(|| {
+            black_box(&xs)
+                .iter()
+                .map(|&x| exact.y_at_x(x).unwrap())
+                .collect::<Vec<_>>()
+        })
+    });
+    c.bench_function("y_at_xs 65536 queries", |b| {
+        b.iter(|| exact.y_at_xs(black_box(&xs)).unwrap())
+    });
+}
+
+#[derive(serde::Deserialize)]
+struct Presorted(#[serde(with = "lerp_table::presorted")] Piecewise);
+
+fn deserialize(c: &mut Criterion) {
+    let json = serde_json::to_string(&table(1 << 20)).unwrap();
+
+    c.bench_function("deserialize 1M knots", |b| {
+        b.iter(|| serde_json::from_str::<Piecewise>(black_box(&json)).unwrap())
+    });
+    c.bench_function("deserialize 1M knots presorted", |b| {
+        b.iter(|| {
+            serde_json::from_str::<Presorted>(black_box(&json))
+                .unwrap()
+                .0
+        })
+    });
+}
+
+criterion_group!(benches, bake, batch, deserialize);
+criterion_main!(benches);
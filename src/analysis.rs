@@ -0,0 +1,704 @@
+use std::fmt;
+
+use ordered_float::NotNan;
+use serde::Serialize;
+
+use crate::{InterpKind, Monotonicity, Piecewise, PiecewiseErr};
+
+/// Which end of a table a scan starts from.
+#[derive(Debug, Clone, Copy, Hash, PartialEq, Eq)]
+pub enum ScanDirection {
+    FromLeft,
+    FromRight,
+}
+
+/// A summary of a table from [`Piecewise::analyze`], for logging.
+///
+/// Shape is judged on the knots as if every segment were linear, like
+/// [`Piecewise::is_convex`]. Zero width segments between repeated knots are skipped, so
+/// a single point table has no slope or width and reports 0 for both.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct PiecewiseReport {
+    pub knots: usize,
+    pub domain: (f64, f64),
+    pub range: (f64, f64),
+    /// The steepest slope of any segment, as an absolute value.
+    pub max_slope: f64,
+    pub min_segment_width: f64,
+    /// The direction the table never goes against, if any. A flat table reports
+    /// [`Monotonicity::NonDecreasing`].
+    pub monotonicity: Option<Monotonicity>,
+    pub convex: bool,
+    pub concave: bool,
+    pub total_variation: f64,
+}
+
+impl fmt::Display for PiecewiseReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let shape = match (self.convex, self.concave) {
+            (true, true) => "straight",
+            (true, false) => "convex",
+            (false, true) => "concave",
+            (false, false) => "neither convex nor concave",
+        };
+        let monotonicity = match self.monotonicity {
+            Some(Monotonicity::NonDecreasing) => "non-decreasing",
+            Some(Monotonicity::NonIncreasing) => "non-increasing",
+            None => "not monotonic",
+        };
+        writeln!(f, "knots: {}", self.knots)?;
+        writeln!(f, "domain: [{}, {}]", self.domain.0, self.domain.1)?;
+        writeln!(f, "range: [{}, {}]", self.range.0, self.range.1)?;
+        writeln!(f, "max slope: {}", self.max_slope)?;
+        writeln!(f, "min segment width: {}", self.min_segment_width)?;
+        writeln!(f, "shape: {monotonicity}, {shape}")?;
+        write!(f, "total variation: {}", self.total_variation)
+    }
+}
+
+impl Piecewise {
+    /// Every statistic of [`PiecewiseReport`], gathered in one pass over the segments.
+    pub fn analyze(&self) -> PiecewiseReport {
+        let data = self.as_slice();
+        let (mut rising, mut falling) = (false, false);
+        let (mut convex, mut concave) = (true, true);
+        let (mut max_slope, mut min_width) = (0.0f64, f64::INFINITY);
+        let mut previous_slope: Option<f64> = None;
+        let (mut min_y, mut max_y) = (data[0].y(), data[0].y());
+        let mut total_variation = 0.0;
+        for pair in data.windows(2) {
+            let (left, right) = (pair[0], pair[1]);
+            min_y = min_y.min(right.y());
+            max_y = max_y.max(right.y());
+            total_variation += (right.y() - left.y()).abs();
+            rising |= right.1 > left.1;
+            falling |= right.1 < left.1;
+            if left.0 == right.0 {
+                continue;
+            }
+            let width = right.x() - left.x();
+            let slope = (right.y() - left.y()) / width;
+            if let Some(previous) = previous_slope {
+                convex &= slope >= previous;
+                concave &= slope <= previous;
+            }
+            previous_slope = Some(slope);
+            max_slope = max_slope.max(slope.abs());
+            min_width = min_width.min(width);
+        }
+
+        PiecewiseReport {
+            knots: data.len(),
+            domain: self.domain(),
+            range: (min_y, max_y),
+            max_slope,
+            min_segment_width: if previous_slope.is_some() {
+                min_width
+            } else {
+                0.0
+            },
+            monotonicity: match (rising, falling) {
+                (_, false) => Some(Monotonicity::NonDecreasing),
+                (false, true) => Some(Monotonicity::NonIncreasing),
+                (true, true) => None,
+            },
+            convex,
+            concave,
+            total_variation,
+        }
+    }
+
+    /// The sorted, disjoint closed intervals of x where `y_at_x(x) >= threshold`.
+    ///
+    /// A curve that only touches the threshold at a single point reports it as a
+    /// zero width `(x, x)` interval. A step that drops below the threshold holds its
+    /// left y until its right knot, so the interval runs up to that knot, while one
+    /// that jumps at its left knot drops there.
+    pub fn support(&self, threshold: f64) -> Result<Vec<(f64, f64)>, PiecewiseErr> {
+        let threshold = NotNan::new(threshold)?.into_inner();
+        let data = self.as_slice();
+        let mut intervals = Vec::new();
+
+        let mut start = (data[0].y() >= threshold).then_some(data[0].x());
+        for (pair, kind) in data.windows(2).zip(self.1.iter()) {
+            let (x1, y1, x2, y2) = (pair[0].x(), pair[0].y(), pair[1].x(), pair[1].y());
+            match (y1 >= threshold, y2 >= threshold) {
+                (true, false) => {
+                    let end = match kind {
+                        InterpKind::StepPrevious => x2,
+                        _ => kind.crossing(x1, y1, x2, y2, threshold),
+                    };
+                    intervals.push((start.take().unwrap_or(x1), end));
+                }
+                (false, true) => start = Some(kind.crossing(x1, y1, x2, y2, threshold)),
+                _ => (),
+            }
+        }
+        if let Some(start) = start {
+            intervals.push((start, data[data.len() - 1].x()));
+        }
+
+        Ok(intervals)
+    }
+
+    /// The first x, scanning from one end, where the table equals `target`.
+    ///
+    /// Works on non-monotonic tables. A flat run at the target reports the end
+    /// closest to where the scan started, so its leftmost point for
+    /// [`ScanDirection::FromLeft`] and its rightmost for [`ScanDirection::FromRight`].
+    /// A step only takes the values of its knots, and holds the left one up to but not
+    /// including the right knot, so it's found at its left knot from either side.
+    pub fn first_x_where(
+        &self,
+        target: f64,
+        direction: ScanDirection,
+    ) -> Result<Option<f64>, PiecewiseErr> {
+        let target = NotNan::new(target)?.into_inner();
+        let data = self.as_slice();
+        let strictly_between =
+            |a: f64, b: f64| (a < target && target < b) || (b < target && target < a);
+
+        let indices: Box<dyn Iterator<Item = usize>> = match direction {
+            ScanDirection::FromLeft => Box::new(0..data.len()),
+            ScanDirection::FromRight => Box::new((0..data.len()).rev()),
+        };
+        for i in indices {
+            if data[i].y() == target {
+                return Ok(Some(data[i].x()));
+            }
+            //the segment leading away from this knot in the scan direction
+            let segment = match direction {
+                ScanDirection::FromLeft => (i + 1 < data.len()).then_some(i),
+                ScanDirection::FromRight => i.checked_sub(1),
+            };
+            if let Some(s) = segment {
+                let (left, right) = (data[s], data[s + 1]);
+                //a step jumps over everything strictly between its knots
+                let step = matches!(self.1[s], InterpKind::StepPrevious | InterpKind::StepNext);
+                if !step && strictly_between(left.y(), right.y()) {
+                    let x = self.1[s].crossing(left.x(), left.y(), right.x(), right.y(), target);
+                    return Ok(Some(x));
+                }
+            }
+        }
+        Ok(None)
+    }
+
+    /// An x within `bracket` where the table equals `f`, found by bisecting a sign
+    /// change of `y_at_x(x) - f(x)` down to a width of `tol`.
+    ///
+    /// The difference is checked at both ends of the bracket and every knot between
+    /// them, and the leftmost interval where it changes sign is refined, so a check
+    /// point that hits the curve exactly is returned as is. An interval whose ends
+    /// share a sign is assumed to hold no root. `bracket` has to lie within the domain,
+    /// and `f` returning NaN errors.
+    pub fn solve_equal<F: Fn(f64) -> f64>(
+        &self,
+        f: F,
+        bracket: (f64, f64),
+        tol: f64,
+    ) -> Result<Option<f64>, PiecewiseErr> {
+        let (low, high) = ordered(bracket.0, bracket.1)?;
+        let tol = NotNan::new(tol)?.into_inner();
+        let (min, max) = self.domain();
+        if low < min || high > max {
+            return Err(PiecewiseErr::NotInDomain);
+        }
+        let gap = |x: f64| Ok::<_, PiecewiseErr>(NotNan::new(self.y_at_x(x)? - f(x))?.into_inner());
+
+        let inner = self
+            .as_slice()
+            .iter()
+            .map(|point| point.x())
+            .filter(|&x| low < x && x < high);
+        let mut previous: Option<(f64, f64)> = None;
+        for x in std::iter::once(low).chain(inner).chain([high]) {
+            let g = gap(x)?;
+            if g == 0.0 {
+                return Ok(Some(x));
+            }
+            match previous {
+                Some((mut lo, g_lo)) if (g_lo < 0.0) != (g < 0.0) => {
+                    let mut hi = x;
+                    loop {
+                        let mid = lo + (hi - lo) / 2.0;
+                        //stopping once the midpoint can't move keeps a tol of 0 finite
+                        if hi - lo <= tol || mid <= lo || mid >= hi {
+                            return Ok(Some(mid));
+                        }
+                        let g_mid = gap(mid)?;
+                        if g_mid == 0.0 {
+                            return Ok(Some(mid));
+                        }
+                        if (g_mid < 0.0) == (g_lo < 0.0) {
+                            lo = mid;
+                        } else {
+                            hi = mid;
+                        }
+                    }
+                }
+                _ => previous = Some((x, g)),
+            }
+        }
+        Ok(None)
+    }
+
+    /// Whether the slopes between successive knots never decrease.
+    ///
+    /// Shape is judged on the knots as if every segment were linear. Tables with
+    /// fewer than 3 distinct x values are both convex and concave.
+    pub fn is_convex(&self) -> bool {
+        self.is_convex_within(0.0)
+    }
+
+    /// Like [`Piecewise::is_convex`], but a slope may drop by up to `eps` from the one
+    /// before it, for noisy data.
+    pub fn is_convex_within(&self, eps: f64) -> bool {
+        self.slopes()
+            .windows(2)
+            .all(|pair| pair[1] >= pair[0] - eps)
+    }
+
+    /// Whether the slopes between successive knots never increase.
+    ///
+    /// Shape is judged on the knots as if every segment were linear. Tables with
+    /// fewer than 3 distinct x values are both convex and concave.
+    pub fn is_concave(&self) -> bool {
+        self.is_concave_within(0.0)
+    }
+
+    /// Like [`Piecewise::is_concave`], but a slope may rise by up to `eps` from the one
+    /// before it, for noisy data.
+    pub fn is_concave_within(&self, eps: f64) -> bool {
+        self.slopes()
+            .windows(2)
+            .all(|pair| pair[1] <= pair[0] + eps)
+    }
+
+    /// The sum of `|y[i + 1] - y[i]|` over consecutive knots.
+    ///
+    /// Every kind of segment is monotonic between its knots, so this is the exact
+    /// total variation of the curve.
+    pub fn total_variation(&self) -> f64 {
+        variation(self.as_slice().iter().map(|point| point.y()))
+    }
+
+    /// The total variation between `a` and `b`, cutting the segments at the ends.
+    ///
+    /// The bounds can come in either order. NaN or out of domain bounds error.
+    pub fn total_variation_between(&self, a: f64, b: f64) -> Result<f64, PiecewiseErr> {
+        let (a, b) = ordered(a, b)?;
+        let points = self.cut(a, b)?;
+        Ok(variation(points.iter().map(|point| point.1)))
+    }
+
+    /// The exact integral from `a` to `b`, negative if `b < a`.
+    ///
+    /// Every kind of segment is integrated in closed form, with a step holding its left
+    /// y over the whole segment. NaN or out of domain bounds error.
+    pub fn integrate(&self, a: f64, b: f64) -> Result<f64, PiecewiseErr> {
+        let sign = if b < a { -1.0 } else { 1.0 };
+        let (a, b) = ordered(a, b)?;
+        self.y_at_x(a)?;
+        self.y_at_x(b)?;
+        let (a, b) = (NotNan::new(a)?, NotNan::new(b)?);
+        let mut area = 0.0;
+        for (pair, &kind) in self.as_slice().windows(2).zip(self.1.iter()) {
+            let (left, right) = (pair[0], pair[1]);
+            let (lo, hi) = (a.max(left.0), b.min(right.0));
+            if lo < hi {
+                area += kind.integral(left, right, lo, hi);
+            }
+        }
+        Ok(sign * area)
+    }
+
+    /// The mean y between `a` and `b`, which may come in either order.
+    ///
+    /// When they're equal this is just the value there. NaN or out of domain bounds error.
+    pub fn average_y(&self, a: f64, b: f64) -> Result<f64, PiecewiseErr> {
+        let (a, b) = ordered(a, b)?;
+        if a == b {
+            return self.y_at_x(a);
+        }
+        Ok(self.integrate(a, b)? / (b - a))
+    }
+
+    /// The smallest and largest y between `a` and `b`, which may come in either order.
+    ///
+    /// Every kind of segment is monotonic between its knots, so the extremes are among
+    /// the values at the bounds and the knots strictly between them. NaN or out of
+    /// domain bounds error.
+    pub fn y_range_over(&self, a: f64, b: f64) -> Result<(f64, f64), PiecewiseErr> {
+        let (a, b) = ordered(a, b)?;
+        let points = self.cut(a, b)?;
+        Ok(points
+            .iter()
+            .fold((f64::INFINITY, f64::NEG_INFINITY), |(min, max), &(_, y)| {
+                (min.min(y), max.max(y))
+            }))
+    }
+
+    /// The `(x, y)` points from `a` to `b`: the table cut at both ends, with the knots
+    /// strictly between. `a` must not be greater than `b`.
+    pub(crate) fn cut(&self, a: f64, b: f64) -> Result<Vec<(f64, f64)>, PiecewiseErr> {
+        let (start, end) = ((a, self.y_at_x(a)?), (b, self.y_at_x(b)?));
+        let inner = self
+            .as_slice()
+            .iter()
+            .filter(|point| a < point.x() && point.x() < b)
+            .map(|point| (point.x(), point.y()));
+        Ok(std::iter::once(start).chain(inner).chain([end]).collect())
+    }
+
+    /// Every x where the table reaches `target`, sorted, with a flat run at the target
+    /// giving both of its ends and a step that jumps over it giving the jump.
+    pub(crate) fn crossings(&self, target: f64) -> Vec<f64> {
+        let data = self.as_slice();
+        if data.len() == 1 {
+            return if data[0].y() == target {
+                vec![data[0].x()]
+            } else {
+                Vec::new()
+            };
+        }
+
+        let mut xs: Vec<f64> = Vec::new();
+        for (pair, kind) in data.windows(2).zip(self.1.iter()) {
+            let (x1, y1, x2, y2) = (pair[0].x(), pair[0].y(), pair[1].x(), pair[1].y());
+            if target < y1.min(y2) || target > y1.max(y2) {
+                continue;
+            }
+            let found = if y1 == y2 {
+                [Some(x1), Some(x2)]
+            } else {
+                [Some(kind.crossing(x1, y1, x2, y2, target)), None]
+            };
+            for x in found.into_iter().flatten() {
+                //neighbouring segments both find the knot they share
+                if xs.last() != Some(&x) {
+                    xs.push(x);
+                }
+            }
+        }
+        xs
+    }
+
+    /// The slope of every segment, skipping the zero width ones between repeated knots.
+    fn slopes(&self) -> Vec<f64> {
+        self.as_slice()
+            .windows(2)
+            .filter(|pair| pair[0].0 != pair[1].0)
+            .map(|pair| (pair[1].y() - pair[0].y()) / (pair[1].x() - pair[0].x()))
+            .collect()
+    }
+}
+
+/// The bounds of an interval given in either order, erroring on NaN.
+fn ordered(a: f64, b: f64) -> Result<(f64, f64), PiecewiseErr> {
+    let (a, b) = (NotNan::new(a)?, NotNan::new(b)?);
+    Ok((a.min(b).into_inner(), a.max(b).into_inner()))
+}
+
+fn variation(ys: impl Iterator<Item = f64>) -> f64 {
+    let mut ys = ys.peekable();
+    let mut total = 0.0;
+    while let (Some(y1), Some(&y2)) = (ys.next(), ys.peek()) {
+        total += (y2 - y1).abs();
+    }
+    total
+}
+
+#[cfg(test)]
+mod tests {
+    use super::ScanDirection::{FromLeft, FromRight};
+    use crate::tests::{table, SIDEARM};
+    use crate::{InterpKind, Piecewise, PiecewiseErr};
+
+    #[test]
+    fn support_of_w_shape() {
+        let w = table(&[(0.0, 10.0), (1.0, 0.0), (2.0, 5.0), (3.0, 0.0), (4.0, 10.0)]);
+        assert_eq!(w.support(6.0).unwrap(), [(0.0, 0.4), (3.6, 4.0)]);
+        assert_eq!(
+            w.support(5.0).unwrap(),
+            [(0.0, 0.5), (2.0, 2.0), (3.5, 4.0)]
+        );
+        assert_eq!(w.support(-1.0).unwrap(), [(0.0, 4.0)]);
+        assert!(w.support(11.0).unwrap().is_empty());
+        assert!(w.support(f64::NAN).is_err());
+    }
+
+    #[test]
+    fn support_of_flat_table() {
+        let flat = table(&[(0.0, 5.0), (10.0, 5.0)]);
+        assert_eq!(flat.support(5.0).unwrap(), [(0.0, 10.0)]);
+        assert!(flat.support(5.5).unwrap().is_empty());
+
+        assert_eq!(table(&[(2.0, 5.0)]).support(5.0).unwrap(), [(2.0, 2.0)]);
+    }
+
+    #[test]
+    fn support_of_steps() {
+        let stairs = table(&[(0.0, 0.0), (1.0, 5.0), (2.0, 0.0), (3.0, 8.0), (4.0, 1.0)])
+            .with_interp(InterpKind::StepPrevious)
+            .unwrap();
+        //the 5 is held over [1, 2), and the 8 over [3, 4)
+        assert_eq!(stairs.support(5.0).unwrap(), [(1.0, 2.0), (3.0, 4.0)]);
+        assert_eq!(stairs.support(6.0).unwrap(), [(3.0, 4.0)]);
+        assert_eq!(stairs.support(0.0).unwrap(), [(0.0, 4.0)]);
+    }
+
+    #[test]
+    fn first_x_where() {
+        let zigzag = table(&[(0.0, 0.0), (1.0, 10.0), (2.0, 0.0), (3.0, 10.0)]);
+        assert_eq!(zigzag.first_x_where(5.0, FromLeft).unwrap(), Some(0.5));
+        assert_eq!(zigzag.first_x_where(5.0, FromRight).unwrap(), Some(2.5));
+        assert_eq!(zigzag.first_x_where(10.0, FromLeft).unwrap(), Some(1.0));
+        assert_eq!(zigzag.first_x_where(10.0, FromRight).unwrap(), Some(3.0));
+        assert_eq!(zigzag.first_x_where(11.0, FromLeft).unwrap(), None);
+        assert!(zigzag.first_x_where(f64::NAN, FromLeft).is_err());
+
+        let plateau = table(&[(0.0, 0.0), (1.0, 5.0), (2.0, 5.0), (3.0, 0.0)]);
+        assert_eq!(plateau.first_x_where(5.0, FromLeft).unwrap(), Some(1.0));
+        assert_eq!(plateau.first_x_where(5.0, FromRight).unwrap(), Some(2.0));
+
+        let stairs = table(&[(0.0, 0.0), (1.0, 10.0), (2.0, 20.0)])
+            .with_interp(InterpKind::StepPrevious)
+            .unwrap();
+        assert_eq!(stairs.first_x_where(5.0, FromLeft).unwrap(), None);
+        assert_eq!(stairs.first_x_where(15.0, FromRight).unwrap(), None);
+        assert_eq!(stairs.first_x_where(10.0, FromLeft).unwrap(), Some(1.0));
+        assert_eq!(stairs.first_x_where(10.0, FromRight).unwrap(), Some(1.0));
+        assert_eq!(stairs.y_at_x(1.0).unwrap(), 10.0);
+    }
+
+    #[test]
+    fn total_variation() {
+        let sidearm = crate::Piecewise::try_from(Vec::from(crate::tests::SIDEARM)).unwrap();
+        assert_eq!(sidearm.total_variation(), 42.0 - 18.0);
+
+        let zigzag = table(&[(0.0, 0.0), (1.0, 10.0), (2.0, 4.0), (3.0, 7.0), (4.0, -1.0)]);
+        assert_eq!(zigzag.total_variation(), 10.0 + 6.0 + 3.0 + 8.0);
+        assert_eq!(zigzag.total_variation_between(0.0, 4.0).unwrap(), 27.0);
+
+        //cut halfway into the first and last segments
+        assert_eq!(
+            zigzag.total_variation_between(0.5, 3.5).unwrap(),
+            5.0 + 6.0 + 3.0 + 4.0
+        );
+        assert_eq!(zigzag.total_variation_between(3.5, 0.5).unwrap(), 18.0);
+        let within = zigzag.total_variation_between(1.2, 1.7).unwrap();
+        assert!((within - 3.0).abs() < 1e-12);
+        assert_eq!(zigzag.total_variation_between(2.0, 2.0).unwrap(), 0.0);
+
+        assert!(zigzag.total_variation_between(-1.0, 2.0).is_err());
+        assert!(zigzag.total_variation_between(0.0, f64::NAN).is_err());
+        assert_eq!(table(&[(1.0, 5.0)]).total_variation(), 0.0);
+    }
+
+    #[test]
+    fn average_y() {
+        let constant = table(&[(0.0, 7.5), (3.0, 7.5), (10.0, 7.5)]);
+        assert_eq!(constant.average_y(1.0, 9.0).unwrap(), 7.5);
+        assert_eq!(constant.average_y(2.0, 3.0).unwrap(), 7.5);
+
+        //a single trapezoid from 18 to 36
+        let sidearm = crate::Piecewise::try_from(Vec::from(crate::tests::SIDEARM)).unwrap();
+        assert_eq!(
+            sidearm.integrate(0.0, 90.0).unwrap(),
+            90.0 * (18.0 + 36.0) / 2.0
+        );
+        assert_eq!(sidearm.average_y(0.0, 90.0).unwrap(), 27.0);
+        assert_eq!(sidearm.average_y(90.0, 0.0).unwrap(), 27.0);
+        assert_eq!(sidearm.integrate(90.0, 0.0).unwrap(), -2430.0);
+        //2430 + 390 across the corner at 90
+        assert_eq!(sidearm.average_y(0.0, 100.0).unwrap(), 28.2);
+
+        assert_eq!(sidearm.average_y(95.0, 95.0).unwrap(), 39.0);
+        assert!(sidearm.average_y(-1.0, 10.0).is_err());
+        assert!(sidearm.average_y(10.0, f64::NAN).is_err());
+        assert!(sidearm.integrate(0.0, 101.0).is_err());
+    }
+
+    #[test]
+    fn integrate_other_kinds() {
+        let close = |a: f64, b: f64| (a - b).abs() <= 1e-12 * b.abs().max(1.0);
+        let stepped = table(&[(0.0, 1.0), (2.0, 3.0), (3.0, 0.0)])
+            .with_interp(InterpKind::StepPrevious)
+            .unwrap();
+        assert_eq!(stepped.integrate(0.0, 3.0).unwrap(), 2.0 + 3.0);
+        assert_eq!(stepped.integrate(1.0, 2.5).unwrap(), 1.0 + 1.5);
+        assert_eq!(stepped.average_y(0.5, 1.5).unwrap(), 1.0);
+
+        //y = e^x from 0 to 2
+        let growth = table(&[(0.0, 1.0), (2.0, 2f64.exp())])
+            .with_interp(InterpKind::LogY)
+            .unwrap();
+        let area = growth.integrate(0.0, 2.0).unwrap();
+        assert!(close(area, 2f64.exp() - 1.0), "{area}");
+        let area = growth.integrate(0.5, 1.0).unwrap();
+        assert!(close(area, 1f64.exp() - 0.5f64.exp()), "{area}");
+
+        //y = x^2 and y = 1 / x
+        let square = table(&[(1.0, 1.0), (3.0, 9.0)])
+            .with_interp(InterpKind::LogLog)
+            .unwrap();
+        let area = square.integrate(1.0, 2.0).unwrap();
+        assert!(close(area, 7.0 / 3.0), "{area}");
+        let inverse = table(&[(1.0, 1.0), (4.0, 0.25)])
+            .with_interp(InterpKind::LogLog)
+            .unwrap();
+        let area = inverse.integrate(2.0, 4.0).unwrap();
+        assert!(close(area, 2f64.ln()), "{area}");
+
+        let flat = table(&[(1.0, 2.0), (5.0, 2.0)])
+            .with_interp(InterpKind::LogY)
+            .unwrap();
+        assert_eq!(flat.integrate(1.0, 5.0).unwrap(), 8.0);
+    }
+
+    #[test]
+    fn y_range_over() {
+        let w = table(&[(0.0, 4.0), (1.0, 0.0), (2.0, 3.0), (3.0, -1.0), (4.0, 4.0)]);
+        //the minimum is the knot at x = 1
+        assert_eq!(w.y_range_over(0.5, 1.5).unwrap(), (0.0, 2.0));
+        assert_eq!(w.y_range_over(0.5, 3.5).unwrap(), (-1.0, 3.0));
+        //both ends on one rising segment
+        assert_eq!(w.y_range_over(3.25, 3.75).unwrap(), (0.25, 2.75));
+        assert_eq!(w.y_range_over(3.75, 3.25).unwrap(), (0.25, 2.75));
+        assert_eq!(w.y_range_over(2.5, 2.5).unwrap(), (1.0, 1.0));
+        assert_eq!(w.y_range_over(0.0, 4.0).unwrap(), w.range());
+
+        assert!(w.y_range_over(-1.0, 2.0).is_err());
+        assert!(w.y_range_over(1.0, f64::NAN).is_err());
+    }
+
+    #[test]
+    fn convexity() {
+        let v = table(&[(0.0, 10.0), (5.0, 0.0), (10.0, 10.0)]);
+        assert!(v.is_convex() && !v.is_concave());
+
+        let inverted = table(&[(0.0, 0.0), (5.0, 10.0), (10.0, 0.0)]);
+        assert!(!inverted.is_convex() && inverted.is_concave());
+
+        let line = table(&[(0.0, 0.0), (1.0, 2.0), (1.0, 2.0), (3.0, 6.0)]);
+        assert!(line.is_convex() && line.is_concave());
+        assert!(table(&[(0.0, 1.0), (1.0, 5.0)]).is_convex());
+
+        let wiggle = table(&[(0.0, 0.0), (1.0, 1.0), (2.0, 0.0), (3.0, 1.0)]);
+        assert!(!wiggle.is_convex() && !wiggle.is_concave());
+
+        //slopes 1, 0.99, 2: convex apart from a little noise
+        let noisy = table(&[(0.0, 0.0), (1.0, 1.0), (2.0, 1.99), (3.0, 3.99)]);
+        assert!(!noisy.is_convex());
+        assert!(noisy.is_convex_within(0.02));
+        assert!(!noisy.is_concave_within(0.02));
+    }
+
+    #[test]
+    fn solve_equal() {
+        let sidearm = Piecewise::try_from(Vec::from(SIDEARM)).unwrap();
+        //18 + 0.2x = 0.01x² on the first segment
+        let quadratic = |x: f64| 0.01 * x * x;
+        let expected = 10.0 + 1900.0f64.sqrt();
+        let x = sidearm
+            .solve_equal(quadratic, (0.0, 100.0), 1e-9)
+            .unwrap()
+            .unwrap();
+        assert!((x - expected).abs() < 1e-9, "{x} != {expected}");
+        let x = sidearm
+            .solve_equal(quadratic, (100.0, 0.0), 0.0)
+            .unwrap()
+            .unwrap();
+        assert!((x - expected).abs() < 1e-12, "{x} != {expected}");
+
+        assert_eq!(
+            sidearm.solve_equal(|_| 0.0, (0.0, 100.0), 1e-9).unwrap(),
+            None
+        );
+        assert_eq!(
+            sidearm.solve_equal(quadratic, (80.0, 100.0), 1e-9).unwrap(),
+            None
+        );
+        assert_eq!(
+            sidearm.solve_equal(|_| 36.0, (10.0, 100.0), 1e-9).unwrap(),
+            Some(90.0)
+        );
+    }
+
+    #[test]
+    fn solve_equal_errors() {
+        let sidearm = Piecewise::try_from(Vec::from(SIDEARM)).unwrap();
+        assert!(matches!(
+            sidearm.solve_equal(
+                |x| if x > 50.0 { f64::NAN } else { 0.0 },
+                (0.0, 100.0),
+                1e-9
+            ),
+            Err(PiecewiseErr::InputNaN(_))
+        ));
+        assert!(matches!(
+            sidearm.solve_equal(|_| 30.0, (-1.0, 50.0), 1e-9),
+            Err(PiecewiseErr::NotInDomain)
+        ));
+        assert!(sidearm
+            .solve_equal(|_| 30.0, (0.0, 50.0), f64::NAN)
+            .is_err());
+    }
+
+    #[test]
+    fn analyze() {
+        use super::PiecewiseReport;
+        use crate::Monotonicity;
+
+        let report = Piecewise::try_from(Vec::from(SIDEARM)).unwrap().analyze();
+        assert_eq!(
+            report,
+            PiecewiseReport {
+                knots: 3,
+                domain: (0.0, 100.0),
+                range: (18.0, 42.0),
+                max_slope: 6.0 / 10.0,
+                min_segment_width: 10.0,
+                monotonicity: Some(Monotonicity::NonDecreasing),
+                convex: true,
+                concave: false,
+                total_variation: 24.0,
+            }
+        );
+        assert_eq!(
+            report.to_string(),
+            "knots: 3\ndomain: [0, 100]\nrange: [18, 42]\nmax slope: 0.6\n\
+             min segment width: 10\nshape: non-decreasing, convex\ntotal variation: 24"
+        );
+        assert_eq!(
+            serde_json::to_string(&report).unwrap(),
+            r#"{"knots":3,"domain":[0.0,100.0],"range":[18.0,42.0],"max_slope":0.6,"min_segment_width":10.0,"monotonicity":"NonDecreasing","convex":true,"concave":false,"total_variation":24.0}"#
+        );
+
+        let single = table(&[(5.0, 2.0)]).analyze();
+        assert_eq!((single.domain, single.range), ((5.0, 5.0), (2.0, 2.0)));
+        assert_eq!((single.max_slope, single.min_segment_width), (0.0, 0.0));
+        assert_eq!(single.total_variation, 0.0);
+
+        let w = table(&[(0.0, 10.0), (1.0, 0.0), (2.0, 5.0), (3.0, 0.0), (4.0, 10.0)]);
+        let report = w.analyze();
+        assert_eq!(report.monotonicity, None);
+        assert_eq!(
+            (report.convex, report.concave),
+            (w.is_convex(), w.is_concave())
+        );
+        assert_eq!(report.max_slope, 10.0);
+        assert_eq!(
+            (report.range, report.total_variation),
+            (w.range(), w.total_variation())
+        );
+
+        //a repeated knot is skipped for slopes but not for the range or variation
+        let repeated = table(&[(0.0, 1.0), (1.0, 3.0), (1.0, 3.0), (2.0, -2.0)]).analyze();
+        assert_eq!(
+            (repeated.range, repeated.total_variation),
+            ((-2.0, 3.0), 7.0)
+        );
+    }
+}
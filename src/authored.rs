@@ -0,0 +1,152 @@
+use serde::de::{self, DeserializeSeed};
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+use crate::{Coord, InterpKind, Knots, Piecewise, PiecewiseErr, PiecewiseTagged};
+
+/// A linear [`Piecewise`] that remembers the order its knots were written in.
+///
+/// Evaluation uses the sorted table like usual, but serialization writes the knots
+/// back in the order they were given, so a file read and written unchanged comes out
+/// identical. Knots added with [`PiecewiseAuthored::push`] go after all the others.
+///
+/// Serializes as `[[x, y], ...]`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct PiecewiseAuthored {
+    table: Piecewise,
+    /// The sorted index of each knot, in authored order.
+    order: Vec<usize>,
+}
+
+impl TryFrom<Vec<Coord>> for PiecewiseAuthored {
+    type Error = PiecewiseErr;
+    fn try_from(points: Vec<Coord>) -> Result<Self, Self::Error> {
+        //tagging each knot with its position gets it through the sort with errors
+        //already pointing at the authored order
+        let tagged = PiecewiseTagged::try_from(points.into_iter().zip(0..).collect::<Vec<_>>())?;
+        let mut order = vec![0; tagged.table().as_slice().len()];
+        for (sorted, (_, &authored)) in tagged.iter_with_metadata().enumerate() {
+            order[authored] = sorted;
+        }
+        Ok(PiecewiseAuthored {
+            table: tagged.into_table(),
+            order,
+        })
+    }
+}
+
+impl PiecewiseAuthored {
+    pub fn y_at_x(&self, value: f64) -> Result<f64, PiecewiseErr> {
+        self.table.y_at_x(value)
+    }
+
+    /// The sorted table.
+    pub fn table(&self) -> &Piecewise {
+        &self.table
+    }
+
+    /// The knots in the order they were written.
+    pub fn authored_points(&self) -> impl Iterator<Item = &Coord> {
+        let data = self.table.as_slice();
+        self.order.iter().map(move |&sorted| &data[sorted])
+    }
+
+    /// Adds a knot after all the existing ones in authored order.
+    ///
+    /// A knot repeating an existing x with a different y errors like
+    /// [`Piecewise::try_from`], with `original_indices` in authored order, and leaves
+    /// the table unchanged.
+    pub fn push(&mut self, point: Coord) -> Result<(), PiecewiseErr> {
+        let data = self.table.as_slice();
+        //after any repeats, like the stable sort on construction
+        let index = data.partition_point(|knot| knot.0 <= point.0);
+        if let Some(&existing) = index.checked_sub(1).map(|i| &data[i]) {
+            if existing.0 == point.0 && existing.1 != point.1 {
+                let authored = self.order.iter().position(|&sorted| sorted == index - 1);
+                return Err(PiecewiseErr::InputUndefined {
+                    x: point.x(),
+                    y_first: existing.y(),
+                    y_second: point.y(),
+                    sorted_index: index - 1,
+                    original_indices: (
+                        authored.expect("every knot has a position"),
+                        self.order.len(),
+                    ),
+                });
+            }
+        }
+
+        let mut points = data.to_vec();
+        points.insert(index, point);
+        let kinds = vec![InterpKind::Linear; points.len() - 1];
+        self.table = Piecewise::from_parts(points, kinds);
+        for sorted in &mut self.order {
+            if *sorted >= index {
+                *sorted += 1;
+            }
+        }
+        self.order.push(index);
+        Ok(())
+    }
+
+    /// Forgets the authored order.
+    pub fn into_table(self) -> Piecewise {
+        self.table
+    }
+}
+
+impl Serialize for PiecewiseAuthored {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_seq(self.authored_points())
+    }
+}
+
+impl<'de> Deserialize<'de> for PiecewiseAuthored {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let points = Knots { finite: true }.deserialize(deserializer)?;
+        PiecewiseAuthored::try_from(points).map_err(de::Error::custom)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::PiecewiseAuthored;
+    use crate::tests::SIDEARM;
+    use crate::{Coord, PiecewiseErr};
+
+    #[test]
+    fn serde_keeps_authored_order() {
+        let json = "[[90.0,36.0],[0.0,18.0],[100.0,42.0],[50.0,28.0]]";
+        let authored: PiecewiseAuthored = serde_json::from_str(json).unwrap();
+        assert_eq!(authored.y_at_x(95.0).unwrap(), 39.0);
+        assert_eq!(authored.y_at_x(50.0).unwrap(), 28.0);
+        assert_eq!(authored.table().as_slice()[0], SIDEARM[0]);
+        assert_eq!(serde_json::to_string(&authored).unwrap(), json);
+
+        let conflict = "[[5, 0], [0, 1], [0, 2]]";
+        let err = serde_json::from_str::<PiecewiseAuthored>(conflict).unwrap_err();
+        assert!(err.to_string().contains("input indices 1 and 2"), "{err}");
+    }
+
+    #[test]
+    fn push_appends() {
+        let mut authored = PiecewiseAuthored::try_from(vec![SIDEARM[2], SIDEARM[0]]).unwrap();
+        authored.push(SIDEARM[1]).unwrap();
+        authored.push(SIDEARM[0]).unwrap();
+        assert_eq!(
+            serde_json::to_string(&authored).unwrap(),
+            "[[100.0,42.0],[0.0,18.0],[90.0,36.0],[0.0,18.0]]"
+        );
+        assert_eq!(authored.y_at_x(95.0).unwrap(), 39.0);
+
+        let conflict = Coord::try_from((90.0, 1.0)).unwrap();
+        assert!(matches!(
+            authored.push(conflict),
+            Err(PiecewiseErr::InputUndefined {
+                sorted_index: 2,
+                original_indices: (2, 4),
+                ..
+            })
+        ));
+        assert_eq!(authored.authored_points().count(), 4);
+    }
+}
@@ -0,0 +1,340 @@
+use ordered_float::NotNan;
+
+use crate::{Coord, Piecewise, PiecewiseErr};
+
+/// Which x [`Piecewise::invert_baked`] records when a y is reached more than once.
+#[derive(Debug, Clone, Copy, Hash, PartialEq, Eq)]
+pub enum CrossingPick {
+    /// The smallest x.
+    First,
+    /// The largest x.
+    Last,
+    /// The x closest to the one picked for the previous sample, so the inverse jumps
+    /// as little as possible, with ties going to the smaller x. The first sample takes
+    /// the smallest x.
+    NearestToPrevious,
+}
+
+/// A table pre-evaluated onto a uniform grid, answering queries without a search.
+///
+/// This approximates the source table, since knots that don't fall on the grid get
+/// their corners cut: the error is bounded by the grid spacing times the steepest
+/// slope of the source. Use [`Piecewise::max_bake_error`] to measure the actual
+/// error of a linear source when picking a resolution.
+#[derive(Debug, Clone, PartialEq)]
+pub struct BakedPiecewise {
+    min_x: f64,
+    max_x: f64,
+    step: f64,
+    values: Vec<f64>,
+}
+
+impl Piecewise {
+    /// Samples the table at `resolution` evenly spaced x values spanning its domain.
+    ///
+    /// `resolution` has to be at least 2, unless the table is a single point. A domain
+    /// too wide for its spacing to be finite, or a resolution so fine that neighbouring
+    /// samples round to the same x, errors with [`PiecewiseErr::InvalidResolution`].
+    pub fn bake(&self, resolution: usize) -> Result<BakedPiecewise, PiecewiseErr> {
+        let (min_x, max_x) = self.domain();
+        if min_x == max_x {
+            return Ok(BakedPiecewise {
+                min_x,
+                max_x,
+                step: 0.0,
+                values: vec![self.as_slice()[0].y()],
+            });
+        }
+        if resolution < 2 {
+            return Err(PiecewiseErr::InvalidResolution(resolution));
+        }
+
+        let step = (max_x - min_x) / (resolution - 1) as f64;
+        if !step.is_finite() {
+            return Err(PiecewiseErr::InvalidResolution(resolution));
+        }
+        let mut values = Vec::with_capacity(resolution);
+        let mut previous = f64::NEG_INFINITY;
+        for i in 0..resolution {
+            let x = grid_x(min_x, max_x, step, resolution, i);
+            if !x.is_finite() || x <= previous {
+                return Err(PiecewiseErr::InvalidResolution(resolution));
+            }
+            previous = x;
+            values.push(self.y_at_x(x)?);
+        }
+
+        Ok(BakedPiecewise {
+            min_x,
+            max_x,
+            step,
+            values,
+        })
+    }
+
+    /// The exact largest difference between this table and its bake at `resolution`,
+    /// for picking a resolution. Like [`BakedPiecewise::max_bake_error`], the table
+    /// has to be linear.
+    pub fn max_bake_error(&self, resolution: usize) -> Result<f64, PiecewiseErr> {
+        self.check_linear()?;
+        self.bake(resolution)?.max_bake_error(self)
+    }
+
+    /// An approximate inverse: a table from y to x, sampled at `resolution` evenly
+    /// spaced y values spanning the range of the table.
+    ///
+    /// Where a y is reached at several x, `pick` chooses between them. Steps never
+    /// reach the values they jump over, so those samples take the x of the jump.
+    /// `resolution` has to be at least 2, unless the table is constant, and a range too
+    /// wide for its spacing to be finite errors with [`PiecewiseErr::InvalidResolution`].
+    /// A sample the table still misses, through rounding, errors with
+    /// [`PiecewiseErr::NotInDomain`] rather than being clamped to a y that is reached.
+    pub fn invert_baked(
+        &self,
+        resolution: usize,
+        pick: CrossingPick,
+    ) -> Result<Piecewise, PiecewiseErr> {
+        let (min_y, max_y) = self.range();
+        let targets = if min_y == max_y {
+            vec![min_y]
+        } else if resolution < 2 {
+            return Err(PiecewiseErr::InvalidResolution(resolution));
+        } else {
+            let step = (max_y - min_y) / (resolution - 1) as f64;
+            if !step.is_finite() {
+                return Err(PiecewiseErr::InvalidResolution(resolution));
+            }
+            (0..resolution)
+                .map(|i| {
+                    if i == resolution - 1 {
+                        max_y
+                    } else {
+                        min_y + step * i as f64
+                    }
+                })
+                .collect()
+        };
+
+        let mut previous: Option<f64> = None;
+        let points = targets
+            .into_iter()
+            .map(|y| {
+                let xs = self.crossings(y);
+                let x = match (pick, previous) {
+                    (CrossingPick::Last, _) => xs.last().copied(),
+                    (CrossingPick::NearestToPrevious, Some(previous)) => xs
+                        .iter()
+                        .copied()
+                        .min_by(|a, b| (a - previous).abs().total_cmp(&(b - previous).abs())),
+                    _ => xs.first().copied(),
+                }
+                .ok_or(PiecewiseErr::NotInDomain)?;
+                previous = Some(x);
+                Ok(Coord::try_from((y, x))?)
+            })
+            .collect::<Result<Vec<_>, PiecewiseErr>>()?;
+        Piecewise::try_from(points)
+    }
+}
+
+impl BakedPiecewise {
+    /// Like [`Piecewise::y_at_x`], NaN and out of domain values error.
+    pub fn y_at_x(&self, value: f64) -> Result<f64, PiecewiseErr> {
+        let value = NotNan::new(value)?.into_inner();
+        if value < self.min_x || value > self.max_x {
+            return Err(PiecewiseErr::NotInDomain);
+        }
+        if self.values.len() == 1 {
+            return Ok(self.values[0]);
+        }
+
+        let position = (value - self.min_x) / self.step;
+        //max_x itself would index one past the last segment
+        let index = (position as usize).min(self.values.len() - 2);
+        let t = position - index as f64;
+        let (y1, y2) = (self.values[index], self.values[index + 1]);
+        Ok(y1 + t * (y2 - y1))
+    }
+
+    /// The grid as a plain table, which evaluates identically up to rounding.
+    pub fn to_piecewise(&self) -> Result<Piecewise, PiecewiseErr> {
+        let len = self.values.len();
+        let points = self
+            .values
+            .iter()
+            .enumerate()
+            .map(|(i, &y)| Coord::try_from((grid_x(self.min_x, self.max_x, self.step, len, i), y)))
+            .collect::<Result<Vec<_>, _>>()?;
+        Piecewise::try_from(points)
+    }
+
+    /// The exact largest difference between this and a linear `source` over their
+    /// shared domain.
+    ///
+    /// The maximum is only known exactly when both sides are linear, so a source of
+    /// another kind errors with [`PiecewiseErr::UnsupportedInterp`].
+    pub fn max_bake_error(&self, source: &Piecewise) -> Result<f64, PiecewiseErr> {
+        self.to_piecewise()?.max_abs_difference(source)
+    }
+
+    pub fn len(&self) -> usize {
+        self.values.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.values.is_empty()
+    }
+}
+
+/// The x of sample `i` of `len`, with the last landing exactly on `max_x` rather than
+/// wherever rounding puts it.
+fn grid_x(min_x: f64, max_x: f64, step: f64, len: usize, i: usize) -> f64 {
+    if i == len - 1 {
+        max_x
+    } else {
+        min_x + step * i as f64
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::CrossingPick;
+    use crate::tests::{table, SIDEARM};
+    use crate::ScanDirection::FromLeft;
+    use crate::{InterpKind, Piecewise, PiecewiseErr};
+
+    #[test]
+    fn baked_matches_exact() {
+        let exact = Piecewise::try_from(Vec::from(SIDEARM)).unwrap();
+        let baked = exact.bake(64).unwrap();
+        let bound = baked.max_bake_error(&exact).unwrap();
+        //spacing times the change in slope at the 90 knot
+        assert!(bound > 0.0 && bound <= (100.0 / 63.0) * (0.6 - 0.2));
+
+        //a small LCG so the queries are spread out but repeatable
+        let mut state: u64 = 0x2545_f491_4f6c_dd1d;
+        for _ in 0..10_000 {
+            state = state.wrapping_mul(6364136223846793005).wrapping_add(1);
+            let x = (state >> 11) as f64 / (1u64 << 53) as f64 * 100.0;
+            let error = (baked.y_at_x(x).unwrap() - exact.y_at_x(x).unwrap()).abs();
+            assert!(error <= bound + 1e-12, "{x}: {error} > {bound}");
+        }
+
+        assert_eq!(baked.y_at_x(0.0).unwrap(), 18.0);
+        assert_eq!(baked.y_at_x(100.0).unwrap(), 42.0);
+        assert!(baked.y_at_x(100.5).is_err());
+        assert!(baked.y_at_x(f64::NAN).is_err());
+    }
+
+    #[test]
+    fn bake_on_knots_is_exact() {
+        let exact = Piecewise::try_from(Vec::from(SIDEARM)).unwrap();
+        let baked = exact.bake(11).unwrap();
+        assert_eq!(baked.max_bake_error(&exact).unwrap(), 0.0);
+
+        let stepped = exact.with_interp(InterpKind::StepPrevious).unwrap();
+        assert!(matches!(
+            stepped.bake(11).unwrap().max_bake_error(&stepped),
+            Err(PiecewiseErr::UnsupportedInterp(InterpKind::StepPrevious))
+        ));
+    }
+
+    #[test]
+    fn invert_baked_monotone() {
+        let sidearm = Piecewise::try_from(Vec::from(SIDEARM)).unwrap();
+        let inverse = sidearm.invert_baked(49, CrossingPick::First).unwrap();
+        assert_eq!(inverse.domain(), (18.0, 42.0));
+
+        //the grid spacing in y times the steeper slope of the exact inverse
+        let tolerance = (24.0 / 48.0) * (90.0 / 18.0);
+        for i in 0..=240 {
+            let y = 18.0 + i as f64 * 0.1;
+            let exact = sidearm.first_x_where(y, FromLeft).unwrap().unwrap();
+            let error = (inverse.y_at_x(y).unwrap() - exact).abs();
+            assert!(error <= tolerance, "{y}: {error}");
+        }
+        //36 is a sample, so the corner is kept exactly
+        assert_eq!(inverse.y_at_x(36.0).unwrap(), 90.0);
+    }
+
+    #[test]
+    fn invert_baked_picks() {
+        //up, down and up again: y = 5 is reached at x = 0.5, 1.5 and 2.5
+        let zigzag = table(&[(0.0, 0.0), (1.0, 10.0), (2.0, 0.0), (3.0, 10.0)]);
+        let first = zigzag.invert_baked(3, CrossingPick::First).unwrap();
+        assert_eq!(first, table(&[(0.0, 0.0), (5.0, 0.5), (10.0, 1.0)]));
+        let last = zigzag.invert_baked(3, CrossingPick::Last).unwrap();
+        assert_eq!(last, table(&[(0.0, 2.0), (5.0, 2.5), (10.0, 3.0)]));
+
+        let falling = table(&[(0.0, 10.0), (1.0, 0.0), (2.0, 10.0)]);
+        let nearest = falling
+            .invert_baked(3, CrossingPick::NearestToPrevious)
+            .unwrap();
+        assert_eq!(nearest, table(&[(0.0, 1.0), (5.0, 0.5), (10.0, 0.0)]));
+
+        //values a step jumps over map to the jump
+        let mut stepped = table(&[(0.0, 0.0), (1.0, 0.0), (2.0, 10.0)]);
+        stepped
+            .set_segment_kind(1, crate::InterpKind::StepPrevious)
+            .unwrap();
+        let inverse = stepped.invert_baked(3, CrossingPick::First).unwrap();
+        assert_eq!(inverse, table(&[(0.0, 0.0), (5.0, 2.0), (10.0, 2.0)]));
+
+        assert!(zigzag.invert_baked(1, CrossingPick::First).is_err());
+        let wide = table(&[(0.0, -f64::MAX), (1.0, f64::MAX)]);
+        assert!(matches!(
+            wide.invert_baked(3, CrossingPick::First),
+            Err(PiecewiseErr::InvalidResolution(3))
+        ));
+        let flat = table(&[(0.0, 4.0), (1.0, 4.0)]);
+        assert_eq!(
+            flat.invert_baked(0, CrossingPick::Last).unwrap(),
+            table(&[(4.0, 1.0)])
+        );
+    }
+
+    #[test]
+    fn bake_degenerate() {
+        let exact = Piecewise::try_from(Vec::from(SIDEARM)).unwrap();
+        assert!(matches!(
+            exact.bake(1),
+            Err(PiecewiseErr::InvalidResolution(1))
+        ));
+
+        let point = table(&[(3.0, 4.0)]).bake(0).unwrap();
+        assert_eq!(point.y_at_x(3.0).unwrap(), 4.0);
+        assert!(point.y_at_x(3.5).is_err());
+        assert_eq!(point.to_piecewise().unwrap(), table(&[(3.0, 4.0)]));
+
+        //spacings that overflow, and samples that round onto each other
+        for (points, resolution) in [
+            (&[(-1e308, 0.0), (1.7e308, 1.0)][..], 3),
+            (&[(4.0, 1.5), (f64::INFINITY, 8.0)][..], 3),
+            (&[(1e16, 0.0), (1e16 + 2.0, 1.0)][..], 1000),
+        ] {
+            assert!(matches!(
+                table(points).bake(resolution),
+                Err(PiecewiseErr::InvalidResolution(r)) if r == resolution
+            ));
+        }
+    }
+
+    #[test]
+    fn max_bake_error_of_table() {
+        let exact = Piecewise::try_from(Vec::from(SIDEARM)).unwrap();
+        assert_eq!(exact.max_bake_error(11).unwrap(), 0.0);
+        assert_eq!(
+            exact.max_bake_error(64).unwrap(),
+            exact.bake(64).unwrap().max_bake_error(&exact).unwrap()
+        );
+        assert!(matches!(
+            exact.max_bake_error(1),
+            Err(PiecewiseErr::InvalidResolution(1))
+        ));
+        let stepped = exact.with_interp(InterpKind::StepPrevious).unwrap();
+        assert!(matches!(
+            stepped.max_bake_error(11),
+            Err(PiecewiseErr::UnsupportedInterp(InterpKind::StepPrevious))
+        ));
+    }
+}
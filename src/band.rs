@@ -0,0 +1,160 @@
+use serde::{de, Deserialize, Deserializer, Serialize, Serializer};
+
+use crate::{Coord, Piecewise, PiecewiseErr};
+
+/// A pair of linear tables bounding a range of y at every x, such as the lowest and
+/// highest damage roll at each distance.
+///
+/// Both bounds are re-knotted onto the knots of either table within their shared
+/// domain, and the lower one has to be at most the upper one at every knot, which
+/// for linear tables means everywhere in between too.
+///
+/// Serializes as `{"lower": [[x, y], ...], "upper": [[x, y], ...]}`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct PiecewiseBand {
+    lower: Piecewise,
+    upper: Piecewise,
+}
+
+impl PiecewiseBand {
+    /// Pairs up `lower` and `upper` over their shared domain, erroring with
+    /// [`PiecewiseErr::BandCrossed`] at the first knot where `lower` is above `upper`.
+    pub fn new(lower: &Piecewise, upper: &Piecewise) -> Result<Self, PiecewiseErr> {
+        lower.check_linear()?;
+        upper.check_linear()?;
+
+        let knots = lower.union_knots(upper)?;
+        let (mut lows, mut highs) = (
+            Vec::with_capacity(knots.len()),
+            Vec::with_capacity(knots.len()),
+        );
+        for x in knots {
+            let (low, high) = (lower.y_at_x(x)?, upper.y_at_x(x)?);
+            if low > high {
+                return Err(PiecewiseErr::BandCrossed {
+                    x,
+                    lower: low,
+                    upper: high,
+                });
+            }
+            lows.push(Coord::try_from((x, low))?);
+            highs.push(Coord::try_from((x, high))?);
+        }
+        Ok(PiecewiseBand {
+            lower: Piecewise::try_from(lows)?,
+            upper: Piecewise::try_from(highs)?,
+        })
+    }
+
+    /// The lower and upper bound at `x`.
+    pub fn bounds_at_x(&self, x: f64) -> Result<(f64, f64), PiecewiseErr> {
+        Ok((self.lower.y_at_x(x)?, self.upper.y_at_x(x)?))
+    }
+
+    /// How far apart the bounds are at `x`, never negative.
+    pub fn width_at_x(&self, x: f64) -> Result<f64, PiecewiseErr> {
+        let (low, high) = self.bounds_at_x(x)?;
+        Ok(high - low)
+    }
+
+    /// Whether `y` is within the bounds at `x`, counting the bounds themselves.
+    pub fn contains(&self, x: f64, y: f64) -> Result<bool, PiecewiseErr> {
+        let (low, high) = self.bounds_at_x(x)?;
+        Ok(low <= y && y <= high)
+    }
+
+    pub fn lower(&self) -> &Piecewise {
+        &self.lower
+    }
+
+    pub fn upper(&self) -> &Piecewise {
+        &self.upper
+    }
+}
+
+#[derive(Serialize, Deserialize)]
+struct BandRepr<T> {
+    lower: T,
+    upper: T,
+}
+
+impl Serialize for PiecewiseBand {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        BandRepr {
+            lower: &self.lower,
+            upper: &self.upper,
+        }
+        .serialize(serializer)
+    }
+}
+
+impl<'de> Deserialize<'de> for PiecewiseBand {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let BandRepr { lower, upper } = BandRepr::<Piecewise>::deserialize(deserializer)?;
+        PiecewiseBand::new(&lower, &upper).map_err(de::Error::custom)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::PiecewiseBand;
+    use crate::tests::{table, SIDEARM};
+    use crate::{Piecewise, PiecewiseErr};
+
+    #[test]
+    fn valid_band() {
+        let lower = Piecewise::try_from(Vec::from(SIDEARM)).unwrap();
+        let upper = table(&[(0.0, 24.0), (50.0, 40.0), (100.0, 48.0)]);
+        let band = PiecewiseBand::new(&lower, &upper).unwrap();
+
+        //both sides now have the knots of both tables
+        assert_eq!(band.lower().as_slice().len(), 4);
+        assert_eq!(
+            band.lower(),
+            &table(&[(0.0, 18.0), (50.0, 28.0), (90.0, 36.0), (100.0, 42.0)])
+        );
+        assert_eq!(band.bounds_at_x(50.0).unwrap(), (28.0, 40.0));
+        assert_eq!(band.width_at_x(0.0).unwrap(), 6.0);
+        assert!(band.bounds_at_x(101.0).is_err());
+
+        let json = serde_json::to_string(&band).unwrap();
+        assert!(
+            json.starts_with(r#"{"lower":[[0.0,18.0],[50.0,28.0]"#),
+            "{json}"
+        );
+        let back: PiecewiseBand = serde_json::from_str(&json).unwrap();
+        assert_eq!(back, band);
+    }
+
+    #[test]
+    fn crossing_pair() {
+        let rising = table(&[(0.0, 0.0), (10.0, 10.0)]);
+        let flat = table(&[(0.0, 5.0), (4.0, 5.0), (10.0, 5.0)]);
+        assert!(matches!(
+            PiecewiseBand::new(&rising, &flat),
+            Err(PiecewiseErr::BandCrossed { x, lower, upper })
+                if x == 10.0 && lower == 10.0 && upper == 5.0
+        ));
+
+        let json = r#"{"lower": [[0, 1], [1, 3]], "upper": [[0, 2], [1, 2]]}"#;
+        let err = serde_json::from_str::<PiecewiseBand>(json).unwrap_err();
+        assert!(err.to_string().contains("at x = 1"), "{err}");
+    }
+
+    #[test]
+    fn containment() {
+        let band = PiecewiseBand::new(
+            &table(&[(0.0, 0.0), (10.0, 10.0)]),
+            &table(&[(0.0, 2.0), (10.0, 20.0)]),
+        )
+        .unwrap();
+        assert!(band.contains(5.0, 5.0).unwrap());
+        assert!(band.contains(5.0, 11.0).unwrap());
+        assert!(band.contains(5.0, 8.0).unwrap());
+        assert!(!band.contains(5.0, 4.999).unwrap());
+        assert!(!band.contains(5.0, 11.001).unwrap());
+        assert!(band.contains(0.0, 0.0).unwrap() && band.contains(10.0, 20.0).unwrap());
+        assert!(!band.contains(5.0, f64::NAN).unwrap());
+        assert!(band.contains(-1.0, 0.0).is_err());
+    }
+}
@@ -0,0 +1,250 @@
+use crate::{Piecewise, PiecewiseErr};
+
+impl Piecewise {
+    /// Evaluates every x in `xs`, erroring like [`Piecewise::y_at_x`] on the first one
+    /// that does.
+    ///
+    /// With the `simd` feature, large batches on linear tables interpolate four
+    /// queries at a time.
+    pub fn y_at_xs(&self, xs: &[f64]) -> Result<Vec<f64>, PiecewiseErr> {
+        let mut out = vec![0.0; xs.len()];
+        self.y_at_xs_into(xs, &mut out)?;
+        Ok(out)
+    }
+
+    /// [`Piecewise::y_at_xs`] writing into `out`, which has to be as long as `xs`.
+    /// Returns how many values were written.
+    ///
+    /// Like [`Piecewise::y_at_x`] this never allocates or panics. On error the contents
+    /// of `out` are unspecified.
+    pub fn y_at_xs_into(&self, xs: &[f64], out: &mut [f64]) -> Result<usize, PiecewiseErr> {
+        if out.len() != xs.len() {
+            return Err(PiecewiseErr::LengthMismatch {
+                expected: xs.len(),
+                found: out.len(),
+            });
+        }
+        #[cfg(feature = "simd")]
+        if xs.len() >= simd::MIN_BATCH
+            && self.1.iter().all(|&kind| kind == crate::InterpKind::Linear)
+        {
+            simd::y_at_xs_into(self, xs, out)?;
+            return Ok(xs.len());
+        }
+        for (slot, &x) in out.iter_mut().zip(xs) {
+            *slot = self.y_at_x(x)?;
+        }
+        Ok(xs.len())
+    }
+}
+
+#[cfg(feature = "simd")]
+mod simd {
+    use ordered_float::NotNan;
+
+    use crate::{locate, Piecewise, PiecewiseErr, Position};
+
+    const LANES: usize = 4;
+    /// Below this the setup isn't worth it.
+    pub(super) const MIN_BATCH: usize = 64;
+
+    //segments are found one lane at a time, then the lerps run as straight line code
+    //over fixed size arrays so they compile down to vector instructions. The math is
+    //the same as the scalar lerp, so results match it exactly.
+    pub(super) fn y_at_xs_into(
+        table: &Piecewise,
+        xs: &[f64],
+        out: &mut [f64],
+    ) -> Result<(), PiecewiseErr> {
+        let data = table.as_slice();
+
+        let mut chunks = xs.chunks_exact(LANES);
+        let mut slots = out.chunks_exact_mut(LANES);
+        for (chunk, slot) in (&mut chunks).zip(&mut slots) {
+            let mut x1 = [0.0; LANES];
+            let mut y1 = [0.0; LANES];
+            let mut x2 = [0.0; LANES];
+            let mut y2 = [0.0; LANES];
+            let mut knot = [false; LANES];
+            for lane in 0..LANES {
+                let (left, right) = match locate(data, NotNan::new(chunk[lane])?)? {
+                    Position::Knot(i) => {
+                        knot[lane] = true;
+                        (data[i], data[i])
+                    }
+                    Position::Between(i) => (data[i - 1], data[i]),
+                };
+                (x1[lane], y1[lane]) = (left.x(), left.y());
+                (x2[lane], y2[lane]) = (right.x(), right.y());
+            }
+
+            let mut ys = [0.0; LANES];
+            for lane in 0..LANES {
+                let slope = (y1[lane] - y2[lane]) / (x1[lane] - x2[lane]);
+                let y = slope * (chunk[lane] - x1[lane]) + y1[lane];
+                //knot lanes divided by zero above, so they take the knot's y instead
+                ys[lane] = if knot[lane] { y1[lane] } else { y };
+            }
+            //overflow is rare enough to leave to the scalar path, which reports it
+            for lane in 0..LANES {
+                if !ys[lane].is_finite() {
+                    ys[lane] = table.y_at_x(chunk[lane])?;
+                }
+            }
+            slot.copy_from_slice(&ys);
+        }
+        for (slot, &x) in slots.into_remainder().iter_mut().zip(chunks.remainder()) {
+            *slot = table.y_at_x(x)?;
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::alloc::{GlobalAlloc, Layout, System};
+    use std::cell::Cell;
+    use std::hint::black_box;
+
+    use crate::tests::table;
+    use crate::{Coord, Extrapolate, InterpKind, Piecewise, PiecewiseErr};
+
+    //counted per thread so tests running alongside don't interfere
+    thread_local! {
+        static ALLOCATIONS: Cell<usize> = const { Cell::new(0) };
+    }
+
+    struct Counting;
+
+    unsafe impl GlobalAlloc for Counting {
+        unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+            //thread locals can already be gone while a thread exits
+            let _ = ALLOCATIONS.try_with(|count| count.set(count.get() + 1));
+            System.alloc(layout)
+        }
+
+        unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+            System.dealloc(ptr, layout)
+        }
+    }
+
+    #[global_allocator]
+    static ALLOCATOR: Counting = Counting;
+
+    fn allocations_during(f: impl FnOnce()) -> usize {
+        let before = ALLOCATIONS.with(Cell::get);
+        f();
+        ALLOCATIONS.with(Cell::get) - before
+    }
+
+    fn ulps_apart(a: f64, b: f64) -> u64 {
+        (a.to_bits() as i64).abs_diff(b.to_bits() as i64)
+    }
+
+    #[test]
+    fn batch_matches_scalar() {
+        //a small LCG so the tables and queries are spread out but repeatable
+        let mut state: u64 = 0x9e37_79b9_7f4a_7c15;
+        let mut next = || {
+            state = state.wrapping_mul(6364136223846793005).wrapping_add(1);
+            (state >> 11) as f64 / (1u64 << 53) as f64
+        };
+
+        for knots in [1, 2, 7, 200] {
+            let points = (0..knots)
+                .map(|i| Coord::try_from((i as f64 * 3.0 + next(), next() * 200.0 - 100.0)))
+                .collect::<Result<Vec<_>, _>>()
+                .unwrap();
+            let table = Piecewise::try_from(points).unwrap();
+            let (min_x, max_x) = table.domain();
+
+            let mut xs = (0..1003)
+                .map(|_| min_x + next() * (max_x - min_x))
+                .collect::<Vec<_>>();
+            //knots themselves, including both ends
+            xs.extend(table.as_slice().iter().map(|point| point.x()));
+
+            let batch = table.y_at_xs(&xs).unwrap();
+            for (&x, &y) in xs.iter().zip(&batch) {
+                let scalar = table.y_at_x(x).unwrap();
+                assert!(ulps_apart(y, scalar) <= 1, "{x}: {y} != {scalar}");
+            }
+        }
+    }
+
+    #[test]
+    fn batch_errors() {
+        let linear = table(&[(0.0, 0.0), (10.0, 10.0)]);
+        let mut xs = vec![5.0; 100];
+        assert_eq!(linear.y_at_xs(&xs).unwrap(), vec![5.0; 100]);
+        xs[37] = 11.0;
+        assert!(linear.y_at_xs(&xs).is_err());
+        xs[37] = f64::NAN;
+        assert!(linear.y_at_xs(&xs).is_err());
+        assert!(linear.y_at_xs(&[]).unwrap().is_empty());
+
+        let steep = table(&[(0.0, -1e308), (1e-300, 1e308)]);
+        assert!(matches!(
+            steep.y_at_xs(&[5e-301; 100]),
+            Err(PiecewiseErr::NonFiniteResult { .. })
+        ));
+
+        let stepped = linear.with_interp(InterpKind::StepPrevious).unwrap();
+        assert_eq!(stepped.y_at_xs(&[5.0; 100]).unwrap(), vec![0.0; 100]);
+    }
+
+    #[test]
+    fn into_slice() {
+        let linear = table(&[(0.0, 0.0), (10.0, 10.0)]);
+        let xs = (0..100).map(|i| i as f64 / 10.0).collect::<Vec<_>>();
+        let mut out = vec![0.0; xs.len()];
+        assert_eq!(linear.y_at_xs_into(&xs, &mut out).unwrap(), xs.len());
+        assert_eq!(out, xs);
+        assert!(matches!(
+            linear.y_at_xs_into(&xs, &mut out[1..]),
+            Err(PiecewiseErr::LengthMismatch {
+                expected: 100,
+                found: 99
+            })
+        ));
+        assert_eq!(linear.y_at_xs_into(&[], &mut []).unwrap(), 0);
+    }
+
+    #[test]
+    fn evaluation_does_not_allocate() {
+        let points = (0..200)
+            .map(|i| Coord::try_from((i as f64, (i as f64 * 0.37).sin())).unwrap())
+            .collect::<Vec<_>>();
+        let linear = Piecewise::try_from(points).unwrap();
+        let stepped = linear
+            .clone()
+            .with_interp(InterpKind::StepPrevious)
+            .unwrap();
+        let xs = (0..1000).map(|i| i as f64 * 0.199).collect::<Vec<_>>();
+        let mut bad = xs.clone();
+        bad[500] = f64::NAN;
+        let mut out = vec![0.0; xs.len()];
+
+        let count = allocations_during(|| {
+            for &x in &xs {
+                black_box(linear.y_at_x(x).unwrap());
+                black_box(linear.eval_indexed(x).unwrap());
+                black_box(stepped.y_at_x(x).unwrap());
+                black_box(
+                    linear
+                        .y_at_x_extrapolated(x * 2.0, Extrapolate::Linear)
+                        .unwrap(),
+                );
+            }
+            linear.y_at_xs_into(&xs, &mut out).unwrap();
+            stepped.y_at_xs_into(&xs, &mut out).unwrap();
+
+            black_box(linear.y_at_x(f64::NAN).unwrap_err());
+            black_box(linear.y_at_x(-1.0).unwrap_err());
+            black_box(linear.y_at_xs_into(&bad, &mut out).unwrap_err());
+            black_box(linear.y_at_xs_into(&xs, &mut out[1..]).unwrap_err());
+        });
+        assert_eq!(count, 0);
+    }
+}
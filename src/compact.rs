@@ -0,0 +1,165 @@
+use std::fmt::Write;
+use std::str::FromStr;
+
+use thiserror::Error;
+
+use crate::{Coord, Piecewise, PiecewiseErr};
+
+/// Why a string couldn't be parsed as a [`Piecewise`] in the compact format.
+#[derive(Error, Debug)]
+pub enum TableParseError {
+    #[error("Pair {index} is empty")]
+    EmptyPair { index: usize },
+    #[error("Pair {index} is missing the ':' between x and y")]
+    MissingColon { index: usize },
+    #[error("Pair {index} has {field:?}, which is not a number")]
+    NotANumber { index: usize, field: String },
+    #[error("Pair {index} contains NaN")]
+    NaN { index: usize },
+    #[error("Pair {index} contains an infinite value")]
+    Infinite { index: usize },
+    #[error(transparent)]
+    Invalid(#[from] PiecewiseErr),
+}
+
+//the compact format is `x:y` pairs separated by commas, like `0:18,90:36,100:42`, with
+//whitespace allowed around every number. Infinities are rejected like in the default
+//serde format, so anything written by one can be read by the other.
+impl FromStr for Piecewise {
+    type Err = TableParseError;
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        if s.trim().is_empty() {
+            return Err(PiecewiseErr::InputEmpty.into());
+        }
+
+        let points = s
+            .split(',')
+            .enumerate()
+            .map(|(index, pair)| {
+                let pair = pair.trim();
+                if pair.is_empty() {
+                    return Err(TableParseError::EmptyPair { index });
+                }
+                let (x, y) = pair
+                    .split_once(':')
+                    .ok_or(TableParseError::MissingColon { index })?;
+                let parse = |field: &str| {
+                    let field = field.trim();
+                    f64::from_str(field).map_err(|_| TableParseError::NotANumber {
+                        index,
+                        field: field.to_owned(),
+                    })
+                };
+                let point = Coord::try_from((parse(x)?, parse(y)?))
+                    .map_err(|_| TableParseError::NaN { index })?;
+                if !(point.x().is_finite() && point.y().is_finite()) {
+                    return Err(TableParseError::Infinite { index });
+                }
+                Ok(point)
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+
+        Ok(Piecewise::try_from(points)?)
+    }
+}
+
+impl Piecewise {
+    /// Writes the knots in the compact format read by `str::parse`, e.g. `0:18,90:36`.
+    ///
+    /// The format only holds knots, so a table with segments that aren't linear errors
+    /// with [`PiecewiseErr::UnsupportedInterp`] rather than reading back as a different
+    /// curve. Infinite knots can't be read back either and error with
+    /// [`PiecewiseErr::NonFiniteKnot`].
+    pub fn to_compact_string(&self) -> Result<String, PiecewiseErr> {
+        self.check_linear()?;
+        let mut out = String::new();
+        for (i, point) in self.as_slice().iter().enumerate() {
+            if !(point.x().is_finite() && point.y().is_finite()) {
+                return Err(PiecewiseErr::NonFiniteKnot { index: i });
+            }
+            if i > 0 {
+                out.push(',');
+            }
+            write!(out, "{}:{}", point.0, point.1).expect("writing to a String can't fail");
+        }
+        Ok(out)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::TableParseError;
+    use crate::tests::{table, SIDEARM};
+    use crate::{InterpKind, Piecewise, PiecewiseErr};
+
+    #[test]
+    fn parse_compact() {
+        let expected = Piecewise::try_from(Vec::from(SIDEARM)).unwrap();
+        assert_eq!("0:18,90:36,100:42".parse::<Piecewise>().unwrap(), expected);
+        assert_eq!(
+            " 100 : 42 , 0:18,\t90: 3.6e1 "
+                .parse::<Piecewise>()
+                .unwrap(),
+            expected
+        );
+
+        assert!(matches!(
+            "inf:3,0:1".parse::<Piecewise>(),
+            Err(TableParseError::Infinite { index: 0 })
+        ));
+        assert!(matches!(
+            "0:1, 1:-inf".parse::<Piecewise>(),
+            Err(TableParseError::Infinite { index: 1 })
+        ));
+    }
+
+    #[test]
+    fn parse_compact_errors() {
+        assert!(matches!(
+            "0:18,90:36,".parse::<Piecewise>(),
+            Err(TableParseError::EmptyPair { index: 2 })
+        ));
+        assert!(matches!(
+            "0:18,90 36".parse::<Piecewise>(),
+            Err(TableParseError::MissingColon { index: 1 })
+        ));
+        assert!(matches!(
+            "0:18,x:36".parse::<Piecewise>(),
+            Err(TableParseError::NotANumber { index: 1, field }) if field == "x"
+        ));
+        assert!(matches!(
+            "0:18,90:NaN".parse::<Piecewise>(),
+            Err(TableParseError::NaN { index: 1 })
+        ));
+        assert!(matches!(
+            "0:1,0:2".parse::<Piecewise>(),
+            Err(TableParseError::Invalid(
+                PiecewiseErr::InputUndefined { .. }
+            ))
+        ));
+        assert!(matches!(
+            " ".parse::<Piecewise>(),
+            Err(TableParseError::Invalid(PiecewiseErr::InputEmpty))
+        ));
+    }
+
+    #[test]
+    fn compact_round_trip() {
+        let sidearm = Piecewise::try_from(Vec::from(SIDEARM)).unwrap();
+        assert_eq!(sidearm.to_compact_string().unwrap(), "0:18,90:36,100:42");
+
+        let awkward = table(&[(-0.5, 0.1 + 0.2), (1e-300, 1e300), (7.0, -1.0 / 3.0)]);
+        let text = awkward.to_compact_string().unwrap();
+        assert_eq!(text.parse::<Piecewise>().unwrap(), awkward);
+
+        let stepped = sidearm.with_interp(InterpKind::StepPrevious).unwrap();
+        assert!(matches!(
+            stepped.to_compact_string(),
+            Err(PiecewiseErr::UnsupportedInterp(InterpKind::StepPrevious))
+        ));
+        assert!(matches!(
+            table(&[(0.0, 1.0), (f64::INFINITY, 2.0)]).to_compact_string(),
+            Err(PiecewiseErr::NonFiniteKnot { index: 1 })
+        ));
+    }
+}
@@ -0,0 +1,161 @@
+//! Compact serde representation storing the first knot absolutely and every
+//! following knot as the offset from the one before it.
+//!
+//! Use it with `#[serde(with = "lerp_table::delta_format")]`:
+//!
+//! ```json
+//! {"start": [0, 18], "deltas": [[90, 18], [10, 6]]}
+//! ```
+//!
+//! Only linear tables can be written in this format.
+//! Every x delta has to be positive so the reconstructed knots stay sorted.
+//! Repeated knots can't be expressed as a positive delta, so tables holding them
+//! error on serialize rather than reading back without them. Reconstruction sums
+//! the deltas in `f64`, so round trips are exact only when those sums are.
+
+use serde::{de, ser, Deserialize, Deserializer, Serialize, Serializer};
+
+use crate::{checked_pair, Coord, InterpKind, Piecewise};
+
+#[derive(Serialize, Deserialize)]
+struct DeltaRepr {
+    start: Coord,
+    deltas: Vec<(f64, f64)>,
+}
+
+pub fn serialize<S: Serializer>(table: &Piecewise, serializer: S) -> Result<S::Ok, S::Error> {
+    if table
+        .segment_kinds()
+        .iter()
+        .any(|&kind| kind != InterpKind::Linear)
+    {
+        return Err(ser::Error::custom(
+            "only linear tables can be delta encoded",
+        ));
+    }
+    let data = table.as_slice();
+    let start = data[0];
+    let mut deltas = Vec::with_capacity(data.len() - 1);
+    let mut previous = start;
+    for (index, &point) in data.iter().enumerate().skip(1) {
+        if point.0 == previous.0 {
+            return Err(ser::Error::custom(format!(
+                "the knot at index {index} repeats {point}, which has no positive delta"
+            )));
+        }
+        let delta = (point.x() - previous.x(), point.y() - previous.y());
+        if !delta.0.is_finite() || !delta.1.is_finite() {
+            return Err(ser::Error::custom(format!(
+                "the step from {previous} to {point} has no finite delta"
+            )));
+        }
+        deltas.push(delta);
+        previous = point;
+    }
+
+    DeltaRepr { start, deltas }.serialize(serializer)
+}
+
+pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Piecewise, D::Error> {
+    let DeltaRepr { start, deltas } = DeltaRepr::deserialize(deserializer)?;
+
+    let mut points = Vec::with_capacity(deltas.len() + 1);
+    points.push(start);
+    let (mut x, mut y) = (start.0.into_inner(), start.1.into_inner());
+    for (index, (dx, dy)) in deltas.into_iter().enumerate() {
+        if dx.is_nan() || dx <= 0.0 {
+            return Err(de::Error::custom(format!(
+                "x delta {dx} at index {index} is not positive"
+            )));
+        }
+        x += dx;
+        y += dy;
+        //the sums can overflow even when every delta is finite
+        let point = checked_pair((x, y), true).map_err(|err| {
+            de::Error::custom(format!("the knot at index {} is invalid: {err}", index + 1))
+        })?;
+        points.push(point);
+    }
+
+    Piecewise::try_from(points).map_err(de::Error::custom)
+}
+
+#[cfg(test)]
+mod tests {
+    use serde::{Deserialize, Serialize};
+
+    use crate::tests::SIDEARM;
+    use crate::Piecewise;
+
+    #[derive(Debug, PartialEq, Serialize, Deserialize)]
+    struct Weapon {
+        #[serde(with = "crate::delta_format")]
+        falloff: Piecewise,
+    }
+
+    #[test]
+    fn round_trip() {
+        let weapon = Weapon {
+            falloff: Vec::from(SIDEARM).try_into().unwrap(),
+        };
+        let json = serde_json::to_string(&weapon).unwrap();
+        assert_eq!(
+            json,
+            r#"{"falloff":{"start":[0.0,18.0],"deltas":[[90.0,18.0],[10.0,6.0]]}}"#
+        );
+
+        let back: Weapon = serde_json::from_str(&json).unwrap();
+        assert_eq!(back, weapon);
+        assert_eq!(
+            serde_json::to_string(&back.falloff).unwrap(),
+            serde_json::to_string(&weapon.falloff).unwrap()
+        );
+    }
+
+    #[test]
+    fn rejects_infinite_delta() {
+        for points in [
+            [(0.0, f64::INFINITY), (1.0, f64::INFINITY)],
+            [(0.0, 1.0), (1.0, f64::NEG_INFINITY)],
+            [(-f64::MAX, 1.0), (f64::MAX, 1.0)],
+        ] {
+            let weapon = Weapon {
+                falloff: crate::tests::table(&points),
+            };
+            let err = serde_json::to_string(&weapon).unwrap_err();
+            assert!(err.to_string().contains("has no finite delta"), "{err}");
+        }
+    }
+
+    #[test]
+    fn rejects_repeated_knots() {
+        let weapon = Weapon {
+            falloff: crate::tests::table(&[(0.0, 1.0), (1.0, 2.0), (1.0, 2.0), (2.0, 0.0)]),
+        };
+        let err = serde_json::to_string(&weapon).unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "the knot at index 2 repeats 1,2, which has no positive delta"
+        );
+    }
+
+    #[test]
+    fn rejects_non_positive_delta() {
+        let zero = r#"{"falloff":{"start":[0,18],"deltas":[[90,18],[0,6]]}}"#;
+        let err = serde_json::from_str::<Weapon>(zero).unwrap_err();
+        assert!(err.to_string().contains("index 1 is not positive"));
+
+        let negative = r#"{"falloff":{"start":[0,18],"deltas":[[-5,1]]}}"#;
+        assert!(serde_json::from_str::<Weapon>(negative).is_err());
+
+        let overflowing = r#"{"falloff":{"start":[0,0],"deltas":[[1e308,0],[1e308,0]]}}"#;
+        let err = serde_json::from_str::<Weapon>(overflowing).unwrap_err();
+        assert!(
+            err.to_string()
+                .starts_with("the knot at index 2 is invalid: the x value inf is not finite"),
+            "{err}"
+        );
+        let overflowing = r#"{"falloff":{"start":[0,1e308],"deltas":[[1,1e308]]}}"#;
+        assert!(serde_json::from_str::<Weapon>(overflowing).is_err());
+    }
+}
@@ -0,0 +1,756 @@
+use std::sync::Arc;
+
+use ordered_float::NotNan;
+
+use crate::{locate, Coord, Extrapolate, InterpKind, Piecewise, PiecewiseErr, Position};
+
+/// How [`Piecewise::with_guards`] picks the y of a guard knot.
+#[derive(Debug, Clone, Copy, Hash, PartialEq, Eq)]
+pub enum GuardKind {
+    /// The y of the nearest endpoint, so the table goes flat.
+    Hold,
+    /// Continue the segment at that end of the table, like [`Extrapolate::Linear`].
+    Extend,
+}
+
+impl Piecewise {
+    /// Adds a knot at `x` lying on the current curve, so the shape is unchanged.
+    ///
+    /// Returns the index of the knot, which is the existing one if `x` already is a knot.
+    /// Like [`Piecewise::y_at_x`], a segment that overflows at `x` errors with
+    /// [`PiecewiseErr::NonFiniteResult`] and the table is left untouched.
+    pub fn insert_knot(&mut self, x: f64) -> Result<usize, PiecewiseErr> {
+        let value = NotNan::new(x)?;
+        match locate(self.as_slice(), value)? {
+            Position::Knot(index) => Ok(index),
+            Position::Between(index) => {
+                let kind = self.1[index - 1];
+                let y = kind.interpolate_finite(self.0[index - 1], self.0[index], value)?;
+                let point = Coord(value, NotNan::new(y)?);
+                Arc::make_mut(&mut self.0).insert(index, point);
+                //both halves of the split segment keep its kind
+                Arc::make_mut(&mut self.1).insert(index - 1, kind);
+                Ok(index)
+            }
+        }
+    }
+
+    /// Splits the table into one covering `[min_x, x]` and one covering `[x, max_x]`.
+    ///
+    /// Both halves get a knot at exactly `x`. Splitting at either endpoint leaves a
+    /// single point table on that side.
+    pub fn split_at(&self, x: f64) -> Result<(Piecewise, Piecewise), PiecewiseErr> {
+        let mut whole = self.clone();
+        let index = whole.insert_knot(x)?;
+        let right = Arc::make_mut(&mut whole.0).split_off(index);
+        let right_kinds = Arc::make_mut(&mut whole.1).split_off(index);
+        Arc::make_mut(&mut whole.0).push(right[0]);
+        Ok((whole, Piecewise::from_parts(right, right_kinds)))
+    }
+
+    /// Adds guard knots at the given x below and above the domain, so queries a little
+    /// outside it are answered by plain evaluation.
+    ///
+    /// The guard y values are what [`Piecewise::y_at_x_extrapolated`] gives, with
+    /// [`GuardKind::Hold`] as [`Extrapolate::Clamp`] and [`GuardKind::Extend`] as
+    /// [`Extrapolate::Linear`]. An extending guard segment keeps the kind of the end
+    /// segment it continues, a holding one is linear. A guard that isn't strictly
+    /// outside the domain errors with [`PiecewiseErr::GuardInsideDomain`].
+    pub fn with_guards(
+        mut self,
+        low: Option<(f64, GuardKind)>,
+        high: Option<(f64, GuardKind)>,
+    ) -> Result<Piecewise, PiecewiseErr> {
+        let data = self.as_slice();
+        let (first, last) = (data[0], data[data.len() - 1]);
+        //the end segments, skipping over duplicates of the endpoints
+        let low_kind = data
+            .iter()
+            .position(|point| point.0 != first.0)
+            .map(|i| self.1[i - 1]);
+        let high_kind = data
+            .iter()
+            .rposition(|point| point.0 != last.0)
+            .map(|i| self.1[i]);
+
+        let guard = |x: f64, guard: GuardKind, end: Coord, segment: Option<InterpKind>| {
+            let (policy, kind) = match (guard, segment) {
+                (GuardKind::Extend, Some(kind)) => (Extrapolate::Linear, kind),
+                _ => (Extrapolate::Clamp, InterpKind::Linear),
+            };
+            let point = Coord::try_from((x, self.y_at_x_extrapolated(x, policy)?))?;
+            kind.check(point)?;
+            kind.check(end)?;
+            Ok::<_, PiecewiseErr>((point, kind))
+        };
+
+        let inside = |x: f64| PiecewiseErr::GuardInsideDomain {
+            x,
+            min: first.x(),
+            max: last.x(),
+        };
+        let low = match low {
+            Some((x, kind)) => {
+                if NotNan::new(x)? >= first.0 {
+                    return Err(inside(x));
+                }
+                Some(guard(x, kind, first, low_kind)?)
+            }
+            None => None,
+        };
+        let high = match high {
+            Some((x, kind)) => {
+                if NotNan::new(x)? <= last.0 {
+                    return Err(inside(x));
+                }
+                Some(guard(x, kind, last, high_kind)?)
+            }
+            None => None,
+        };
+
+        if let Some((point, kind)) = low {
+            Arc::make_mut(&mut self.0).insert(0, point);
+            Arc::make_mut(&mut self.1).insert(0, kind);
+        }
+        if let Some((point, kind)) = high {
+            Arc::make_mut(&mut self.0).push(point);
+            Arc::make_mut(&mut self.1).push(kind);
+        }
+        Ok(self)
+    }
+
+    /// Keeps only the knots for which `f` returns true.
+    ///
+    /// Each remaining segment keeps the kind of the original segment starting at its
+    /// left knot. If no knot would be left this errors with [`PiecewiseErr::InputEmpty`] and the
+    /// table is left untouched.
+    pub fn retain<F: FnMut(&Coord) -> bool>(&mut self, f: F) -> Result<(), PiecewiseErr> {
+        *self = self.filtered(f)?;
+        Ok(())
+    }
+
+    /// A copy of the table holding only the knots for which `f` returns true.
+    pub fn filtered<F: FnMut(&Coord) -> bool>(&self, mut f: F) -> Result<Piecewise, PiecewiseErr> {
+        //removing knots can't unsort the rest or introduce conflicts, so no revalidation
+        let kept: Vec<usize> = (0..self.0.len()).filter(|&i| f(&self.0[i])).collect();
+        if kept.is_empty() {
+            return Err(PiecewiseErr::InputEmpty);
+        }
+        let points = kept.iter().map(|&i| self.0[i]).collect();
+        let kinds = kept
+            .windows(2)
+            .map(|pair| self.1[pair[0]])
+            .collect::<Vec<_>>();
+        let table = Piecewise::from_parts(points, kinds);
+        //a merged segment can now span knots a log kind can't take, which is checked again
+        for (pair, kind) in table.0.windows(2).zip(table.1.iter()) {
+            kind.check(pair[0])?;
+            kind.check(pair[1])?;
+        }
+        Ok(table)
+    }
+
+    /// Rounds every knot to the nearest multiple of `x_grid` and `y_grid`, where a grid
+    /// of 0 leaves that axis alone.
+    ///
+    /// Knots snapped onto the same point merge into one. Knots snapped onto the same x
+    /// with different y error with [`PiecewiseErr::InputUndefined`], whose
+    /// `original_indices` are the knots' indices in this table. Segment kinds follow
+    /// the rules of [`Piecewise::filtered`].
+    pub fn snap(&self, x_grid: f64, y_grid: f64) -> Result<Piecewise, PiecewiseErr> {
+        for grid in [x_grid, y_grid] {
+            if !(grid.is_finite() && grid >= 0.0) {
+                return Err(PiecewiseErr::InvalidGrid(grid));
+            }
+        }
+        let round = |value: f64, grid: f64| {
+            if grid == 0.0 {
+                value
+            } else {
+                (value / grid).round() * grid
+            }
+        };
+
+        self.snapped_with(|point| (round(point.x(), x_grid), round(point.y(), y_grid)))
+    }
+
+    /// The same shape stretched or squeezed onto `[new_min, new_max]`.
+    ///
+    /// Every knot x is mapped affinely, with the first knot landing exactly on
+    /// `new_min` and the last exactly on `new_max`, which have to have
+    /// `new_min < new_max` and error with [`PiecewiseErr::InvalidInterval`] otherwise. A
+    /// single point table has no shape to stretch and errors with
+    /// [`PiecewiseErr::DegenerateDomain`]. Knots squeezed onto the same x merge or error
+    /// like with [`Piecewise::snap`].
+    pub fn set_domain(&self, new_min: f64, new_max: f64) -> Result<Piecewise, PiecewiseErr> {
+        let (new_min, new_max) = (NotNan::new(new_min)?, NotNan::new(new_max)?);
+        let (new_min, new_max) = (new_min.into_inner(), new_max.into_inner());
+        if new_min >= new_max {
+            return Err(PiecewiseErr::InvalidInterval {
+                min: new_min,
+                max: new_max,
+            });
+        }
+        let (min, max) = self.domain();
+        if min == max {
+            return Err(PiecewiseErr::DegenerateDomain { x: min });
+        }
+
+        let scale = (new_max - new_min) / (max - min);
+        self.snapped_with(|point| {
+            let x = if point.x() == max {
+                new_max
+            } else {
+                new_min + (point.x() - min) * scale
+            };
+            (x, point.y())
+        })
+    }
+
+    /// The table played backwards, mirrored around the middle of its domain so the knot
+    /// at `min_x + d` ends up at `max_x - d`.
+    ///
+    /// Linear and LogY segments keep their kind, and the two step kinds swap, since a
+    /// step holding its left y plays backwards as one holding its right y.
+    ///
+    /// # Panics
+    /// If the table has a LogLog segment, which has no mirrored counterpart, an
+    /// infinite end to its domain, or knots so close that mirroring rounds them onto
+    /// the same x.
+    pub fn reverse_x(&self) -> Piecewise {
+        let (min, max) = self.domain();
+        assert!(
+            min.is_finite() && max.is_finite(),
+            "reverse_x needs a finite domain"
+        );
+        let data = self.as_slice();
+        let points = data
+            .iter()
+            .rev()
+            .map(|point| {
+                //the ends are swapped exactly rather than through the rounding below
+                let x = match point.x() {
+                    x if x == min => max,
+                    x if x == max => min,
+                    x => min + (max - x),
+                };
+                Coord(
+                    NotNan::new(x).expect("x is within a finite domain"),
+                    point.1,
+                )
+            })
+            .collect::<Vec<_>>();
+        //repeated knots stay repeated, distinct ones have to stay apart
+        let apart = (1..points.len()).all(|i| {
+            points[i - 1].0 < points[i].0
+                || data[points.len() - i].0 == data[points.len() - 1 - i].0
+        });
+        assert!(apart, "knots were rounded together by reverse_x");
+        let kinds = self
+            .1
+            .iter()
+            .rev()
+            .map(|&kind| match kind {
+                InterpKind::Linear | InterpKind::LogY => kind,
+                InterpKind::StepPrevious => InterpKind::StepNext,
+                InterpKind::StepNext => InterpKind::StepPrevious,
+                InterpKind::LogLog => panic!("LogLog segments can't be reversed"),
+            })
+            .collect();
+        Piecewise::from_parts(points, kinds)
+    }
+
+    /// The table mirrored vertically around `pivot`, with every y becoming
+    /// `2 * pivot - y`.
+    ///
+    /// Log kinds have no mirrored counterpart and error with
+    /// [`PiecewiseErr::UnsupportedInterp`].
+    pub fn reflect_y(&self, pivot: f64) -> Result<Piecewise, PiecewiseErr> {
+        let pivot = NotNan::new(pivot)?.into_inner();
+        if let Some(&kind) = self
+            .1
+            .iter()
+            .find(|kind| matches!(kind, InterpKind::LogY | InterpKind::LogLog))
+        {
+            return Err(PiecewiseErr::UnsupportedInterp(kind));
+        }
+        //grouped so a pivot near the limits doesn't overflow before the subtraction,
+        //opposite infinities still come out NaN and error
+        let points = self
+            .as_slice()
+            .iter()
+            .map(|point| Coord::try_from((point.x(), pivot + (pivot - point.y()))))
+            .collect::<Result<Vec<_>, _>>()?;
+        Ok(Piecewise::from_parts(points, self.1.to_vec()))
+    }
+
+    /// A copy of the table with `deltas[i]` added to the y of knot `i`.
+    ///
+    /// `deltas` has to have one value per knot, and a NaN errors with
+    /// [`PiecewiseErr::InputNaNAt`] giving its index. The x values are untouched, but
+    /// knots under a log segment have to stay positive and repeated knots have to be
+    /// moved together.
+    pub fn offset_ys(&self, deltas: &[f64]) -> Result<Piecewise, PiecewiseErr> {
+        let mut offset = self.clone();
+        offset.offset_ys_in_place(deltas)?;
+        Ok(offset)
+    }
+
+    /// [`Piecewise::offset_ys`] in place. On error the table is left untouched.
+    pub fn offset_ys_in_place(&mut self, deltas: &[f64]) -> Result<(), PiecewiseErr> {
+        let data = self.as_slice();
+        if deltas.len() != data.len() {
+            return Err(PiecewiseErr::LengthMismatch {
+                expected: data.len(),
+                found: deltas.len(),
+            });
+        }
+        if let Some(index) = deltas.iter().position(|delta| delta.is_nan()) {
+            return Err(PiecewiseErr::InputNaNAt { index });
+        }
+        let points = data
+            .iter()
+            .zip(deltas)
+            .map(|(point, delta)| Coord::try_from((point.x(), point.y() + delta)))
+            .collect::<Result<Vec<_>, _>>()?;
+
+        for (i, pair) in points.windows(2).enumerate() {
+            if pair[0].0 == pair[1].0 && pair[0].1 != pair[1].1 {
+                return Err(PiecewiseErr::InputUndefined {
+                    x: pair[0].x(),
+                    y_first: pair[0].y(),
+                    y_second: pair[1].y(),
+                    sorted_index: i,
+                    original_indices: (i, i + 1),
+                });
+            }
+            self.1[i].check(pair[0])?;
+            self.1[i].check(pair[1])?;
+        }
+        self.0 = Arc::new(points);
+        Ok(())
+    }
+
+    /// Moves every knot to `round(knot)`, merging and erroring like
+    /// [`Piecewise::snap`]. `round` must not reorder knots.
+    pub(crate) fn snapped_with(
+        &self,
+        round: impl Fn(Coord) -> (f64, f64),
+    ) -> Result<Piecewise, PiecewiseErr> {
+        let data = self.as_slice();
+        let points = data
+            .iter()
+            .map(|&point| Coord::try_from(round(point)))
+            .collect::<Result<Vec<_>, _>>()?;
+
+        //rounding can't reorder knots, only bring neighbours together
+        let mut keep = vec![true; points.len()];
+        let mut sorted_index = 0;
+        for i in 1..points.len() {
+            let (last, point) = (points[i - 1], points[i]);
+            if point.0 == last.0 && point.1 != last.1 {
+                return Err(PiecewiseErr::InputUndefined {
+                    x: point.x(),
+                    y_first: last.y(),
+                    y_second: point.y(),
+                    sorted_index,
+                    original_indices: (i - 1, i),
+                });
+            }
+            //repeats that were already in the table stay, like with try_from
+            if point == last && data[i].0 != data[i - 1].0 {
+                keep[i] = false;
+            } else {
+                sorted_index += 1;
+            }
+        }
+
+        let snapped = Piecewise(Arc::new(points), Arc::clone(&self.1));
+        let mut keep = keep.into_iter();
+        snapped.filtered(|_| keep.next().unwrap_or(true))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::GuardKind::{Extend, Hold};
+    use crate::tests::SIDEARM;
+    use crate::{InterpKind, Piecewise, PiecewiseErr};
+
+    fn ulps_apart(a: f64, b: f64) -> u64 {
+        (a.to_bits() as i64 - b.to_bits() as i64).unsigned_abs()
+    }
+
+    #[test]
+    fn insert_knot_keeps_shape() {
+        let original = Piecewise::try_from(Vec::from(SIDEARM)).unwrap();
+        let mut edited = original.clone();
+
+        assert_eq!(edited.insert_knot(33.0).unwrap(), 1);
+        assert_eq!(edited.insert_knot(95.5).unwrap(), 3);
+        assert_eq!(edited.insert_knot(12.25).unwrap(), 1);
+        assert_eq!(edited.as_slice().len(), 6);
+
+        for i in 0..=1000 {
+            let x = i as f64 / 10.0;
+            let (a, b) = (original.y_at_x(x).unwrap(), edited.y_at_x(x).unwrap());
+            assert!(ulps_apart(a, b) <= 1, "{x}: {a} != {b}");
+        }
+    }
+
+    #[test]
+    fn insert_existing_knot_is_noop() {
+        let mut table = Piecewise::try_from(Vec::from(SIDEARM)).unwrap();
+        assert_eq!(table.insert_knot(90.0).unwrap(), 1);
+        assert_eq!(table.as_slice(), SIDEARM);
+
+        assert!(table.insert_knot(-1.0).is_err());
+        assert!(table.insert_knot(f64::NAN).is_err());
+
+        let mut steep = crate::tests::table(&[(0.0, -1e308), (1e-300, 1e308)]);
+        let before = steep.clone();
+        assert!(matches!(
+            steep.insert_knot(5e-301),
+            Err(PiecewiseErr::NonFiniteResult { .. })
+        ));
+        assert_eq!(steep, before);
+        let mut opposite = crate::tests::table(&[(0.0, f64::NEG_INFINITY), (1.0, f64::INFINITY)]);
+        assert!(matches!(
+            opposite.insert_knot(0.5),
+            Err(PiecewiseErr::NonFiniteResult { .. })
+        ));
+    }
+
+    #[test]
+    fn split_at() {
+        let original = Piecewise::try_from(Vec::from(SIDEARM)).unwrap();
+        for at in [0.0, 33.0, 90.0, 100.0] {
+            let (left, right) = original.split_at(at).unwrap();
+            assert_eq!(left.as_slice().last().unwrap().0, at);
+            assert_eq!(right.as_slice()[0].0, at);
+
+            for i in 0..=1000 {
+                let x = i as f64 / 10.0;
+                let half = if x <= at { &left } else { &right };
+                let (a, b) = (original.y_at_x(x).unwrap(), half.y_at_x(x).unwrap());
+                assert!(ulps_apart(a, b) <= 1, "{at} {x}: {a} != {b}");
+            }
+        }
+
+        let (left, right) = original.split_at(0.0).unwrap();
+        assert_eq!(left.as_slice(), &SIDEARM[..1]);
+        assert_eq!(right.as_slice(), SIDEARM);
+        let (left, right) = original.split_at(100.0).unwrap();
+        assert_eq!(left.as_slice(), SIDEARM);
+        assert_eq!(right.as_slice(), &SIDEARM[2..]);
+
+        assert!(original.split_at(100.5).is_err());
+        assert!(original.split_at(f64::NAN).is_err());
+    }
+
+    #[test]
+    fn retain() {
+        let mut table = Piecewise::try_from(Vec::from(SIDEARM)).unwrap();
+        table.retain(|_| true).unwrap();
+        assert_eq!(table.as_slice(), SIDEARM);
+
+        assert!(matches!(
+            table.retain(|_| false),
+            Err(PiecewiseErr::InputEmpty)
+        ));
+        assert_eq!(table.as_slice(), SIDEARM);
+
+        let inner = table.filtered(|c| c.x() > 0.0 && c.x() < 100.0).unwrap();
+        assert_eq!(inner.as_slice(), &SIDEARM[1..2]);
+
+        table.retain(|c| c.y() != 18.0).unwrap();
+        assert_eq!(table.as_slice(), &SIDEARM[1..]);
+        assert!(table.y_at_x(0.0).is_err());
+    }
+
+    #[test]
+    fn snap() {
+        let jittered = crate::tests::table(&[
+            (1e-9, 17.9999999),
+            (89.99999999, 36.0000001),
+            (100.0000004, 41.99999),
+        ]);
+        assert_eq!(jittered.snap(1.0, 1.0).unwrap().as_slice(), SIDEARM);
+        assert_eq!(jittered.snap(0.5, 0.25).unwrap().as_slice(), SIDEARM);
+        let x_only = jittered.snap(1.0, 0.0).unwrap();
+        assert_eq!(x_only.as_slice()[1].x(), 90.0);
+        assert_eq!(x_only.as_slice()[1].y(), 36.0000001);
+
+        let merging = crate::tests::table(&[(0.0, 0.0), (0.9, 1.0), (1.1, 1.0), (2.0, 2.0)]);
+        let merged = merging.snap(1.0, 0.0).unwrap();
+        assert_eq!(
+            merged,
+            crate::tests::table(&[(0.0, 0.0), (1.0, 1.0), (2.0, 2.0)])
+        );
+    }
+
+    #[test]
+    fn snap_conflicts() {
+        let conflicting = crate::tests::table(&[(0.0, 0.0), (3.0, 3.0), (3.9, 1.0), (4.1, 2.0)]);
+        assert!(matches!(
+            conflicting.snap(1.0, 0.0),
+            Err(PiecewiseErr::InputUndefined {
+                x,
+                y_first,
+                y_second,
+                sorted_index: 2,
+                original_indices: (2, 3),
+            }) if x == 4.0 && y_first == 1.0 && y_second == 2.0
+        ));
+
+        let table = Piecewise::try_from(Vec::from(SIDEARM)).unwrap();
+        assert!(matches!(
+            table.snap(-1.0, 0.0),
+            Err(PiecewiseErr::InvalidGrid(_))
+        ));
+        assert!(table.snap(0.0, f64::NAN).is_err());
+        assert_eq!(table.snap(0.0, 0.0).unwrap(), table);
+    }
+
+    #[test]
+    fn set_domain() {
+        let table = Piecewise::try_from(Vec::from(SIDEARM)).unwrap();
+        let squeezed = table.set_domain(-10.0, 40.0).unwrap();
+        assert_eq!(
+            squeezed,
+            crate::tests::table(&[(-10.0, 18.0), (35.0, 36.0), (40.0, 42.0)])
+        );
+        for t in [0.0, 0.1, 0.25, 0.5, 0.9, 0.95, 1.0] {
+            let expected = table.y_at_x(t * 100.0).unwrap();
+            let y = squeezed.y_at_x(-10.0 + t * 50.0).unwrap();
+            assert!(ulps_apart(y, expected) <= 2, "{t}: {y} != {expected}");
+        }
+
+        let stepped = table.with_interp(InterpKind::StepPrevious).unwrap();
+        let stretched = stepped.set_domain(0.0, 1000.0).unwrap();
+        assert_eq!(stretched.segment_kinds(), stepped.segment_kinds());
+        assert_eq!(stretched.domain(), (0.0, 1000.0));
+    }
+
+    #[test]
+    fn set_domain_errors() {
+        let table = Piecewise::try_from(Vec::from(SIDEARM)).unwrap();
+        assert!(matches!(
+            table.set_domain(1.0, 1.0),
+            Err(PiecewiseErr::InvalidInterval { min: 1.0, max: 1.0 })
+        ));
+        assert!(matches!(
+            table.set_domain(2.0, 1.0),
+            Err(PiecewiseErr::InvalidInterval { min: 2.0, max: 1.0 })
+        ));
+        assert!(matches!(
+            table.set_domain(f64::NAN, 1.0),
+            Err(PiecewiseErr::InputNaN(_))
+        ));
+        assert!(matches!(
+            crate::tests::table(&[(5.0, 1.0)]).set_domain(0.0, 1.0),
+            Err(PiecewiseErr::DegenerateDomain { x: 5.0 })
+        ));
+    }
+
+    #[test]
+    fn reverse_x() {
+        let table = Piecewise::try_from(Vec::from(SIDEARM)).unwrap();
+        let reversed = table.reverse_x();
+        assert_eq!(
+            reversed,
+            crate::tests::table(&[(0.0, 42.0), (10.0, 36.0), (100.0, 18.0)])
+        );
+        assert_eq!(reversed.reverse_x(), table);
+        for x in [0.0, 3.0, 10.0, 42.5, 99.0, 100.0] {
+            assert_eq!(
+                reversed.y_at_x(x).unwrap(),
+                table.y_at_x(100.0 - x).unwrap()
+            );
+        }
+
+        let mut mixed = crate::tests::table(&[(1.0, 1.0), (2.0, 4.0), (4.0, 2.0)])
+            .with_interp(InterpKind::LogY)
+            .unwrap();
+        mixed.set_segment_kind(1, InterpKind::Linear).unwrap();
+        let reversed = mixed.reverse_x();
+        assert_eq!(
+            reversed.segment_kinds(),
+            [InterpKind::Linear, InterpKind::LogY]
+        );
+        let (y, expected) = (reversed.y_at_x(3.5).unwrap(), mixed.y_at_x(1.5).unwrap());
+        assert!(ulps_apart(y, expected) <= 2, "{y} != {expected}");
+        assert_eq!(reversed.reverse_x(), mixed);
+        assert_eq!(
+            crate::tests::table(&[(3.0, 1.0)]).reverse_x().domain(),
+            (3.0, 3.0)
+        );
+        let repeated = crate::tests::table(&[(0.0, 1.0), (1.0, 2.0), (1.0, 2.0), (3.0, 0.0)]);
+        assert_eq!(
+            repeated.reverse_x(),
+            crate::tests::table(&[(0.0, 0.0), (2.0, 2.0), (2.0, 2.0), (3.0, 1.0)])
+        );
+
+        //a step holding its left y plays backwards holding its right one
+        let stepped = Piecewise::try_from(Vec::from(SIDEARM))
+            .unwrap()
+            .with_interp(InterpKind::StepPrevious)
+            .unwrap();
+        let reversed = stepped.reverse_x();
+        assert_eq!(reversed.segment_kinds(), [InterpKind::StepNext; 2]);
+        for x in [0.0, 5.0, 10.0, 10.5, 50.0, 99.0, 100.0] {
+            assert_eq!(
+                reversed.y_at_x(x).unwrap(),
+                stepped.y_at_x(100.0 - x).unwrap(),
+                "{x}"
+            );
+        }
+        assert_eq!(reversed.reverse_x(), stepped);
+        assert_eq!(
+            reversed.integrate(0.0, 100.0).unwrap(),
+            18.0 * 90.0 + 36.0 * 10.0
+        );
+    }
+
+    #[test]
+    fn reflect_y() {
+        let table = Piecewise::try_from(Vec::from(SIDEARM)).unwrap();
+        let reflected = table.reflect_y(30.0).unwrap();
+        assert_eq!(
+            reflected,
+            crate::tests::table(&[(0.0, 42.0), (90.0, 24.0), (100.0, 18.0)])
+        );
+        assert_eq!(reflected.reflect_y(30.0).unwrap(), table);
+        assert!(matches!(
+            table.reflect_y(f64::NAN),
+            Err(PiecewiseErr::InputNaN(_))
+        ));
+        assert!(matches!(
+            table.with_interp(InterpKind::LogY).unwrap().reflect_y(0.0),
+            Err(PiecewiseErr::UnsupportedInterp(InterpKind::LogY))
+        ));
+
+        let infinite = crate::tests::table(&[(0.0, 1.0), (1.0, f64::INFINITY)]);
+        assert!(matches!(
+            infinite.reflect_y(f64::INFINITY),
+            Err(PiecewiseErr::InputNaN(_))
+        ));
+        let near_limit = crate::tests::table(&[(0.0, 1e308), (1.0, f64::INFINITY)]);
+        assert_eq!(
+            near_limit.reflect_y(1e308).unwrap(),
+            crate::tests::table(&[(0.0, 1e308), (1.0, f64::NEG_INFINITY)])
+        );
+        assert_eq!(
+            crate::tests::table(&[(0.0, 1e308)])
+                .reflect_y(1e308)
+                .unwrap(),
+            crate::tests::table(&[(0.0, 1e308)])
+        );
+    }
+
+    #[test]
+    fn offset_ys() {
+        let table = Piecewise::try_from(Vec::from(SIDEARM)).unwrap();
+        let offset = table.offset_ys(&[2.0, 0.0, -2.5]).unwrap();
+        assert_eq!(
+            offset,
+            crate::tests::table(&[(0.0, 20.0), (90.0, 36.0), (100.0, 39.5)])
+        );
+        let xs = |table: &Piecewise| table.points().iter().map(|p| p.x()).collect::<Vec<_>>();
+        assert_eq!(xs(&offset), xs(&table));
+
+        let mut edited = table.clone();
+        edited.offset_ys_in_place(&[2.0, 0.0, -2.5]).unwrap();
+        assert_eq!(edited, offset);
+
+        assert!(matches!(
+            edited.offset_ys_in_place(&[1.0, 1.0]),
+            Err(PiecewiseErr::LengthMismatch {
+                expected: 3,
+                found: 2
+            })
+        ));
+        assert!(matches!(
+            edited.offset_ys_in_place(&[1.0, 1.0, f64::NAN]),
+            Err(PiecewiseErr::InputNaNAt { index: 2 })
+        ));
+        assert!(matches!(
+            table
+                .clone()
+                .with_interp(InterpKind::LogY)
+                .unwrap()
+                .offset_ys(&[0.0, -36.0, 0.0]),
+            Err(PiecewiseErr::NotPositive { x: 90.0, .. })
+        ));
+        assert_eq!(edited, offset);
+    }
+
+    #[test]
+    fn with_guards() {
+        let table = Piecewise::try_from(Vec::from(SIDEARM)).unwrap();
+        let held = table
+            .clone()
+            .with_guards(Some((-10.0, Hold)), Some((120.0, Hold)))
+            .unwrap();
+        assert_eq!(held.domain(), (-10.0, 120.0));
+        assert_eq!(held.y_at_x(-10.0).unwrap(), 18.0);
+        assert_eq!(held.y_at_x(-5.0).unwrap(), 18.0);
+        assert_eq!(held.y_at_x(0.0).unwrap(), 18.0);
+        assert_eq!(held.y_at_x(100.0).unwrap(), 42.0);
+        assert_eq!(held.y_at_x(110.0).unwrap(), 42.0);
+
+        let extended = table
+            .clone()
+            .with_guards(Some((-10.0, Extend)), Some((120.0, Extend)))
+            .unwrap();
+        //slopes of 0.2 and 0.6 carried past the ends
+        assert_eq!(extended.y_at_x(-10.0).unwrap(), 16.0);
+        assert_eq!(extended.y_at_x(-5.0).unwrap(), 17.0);
+        assert_eq!(extended.y_at_x(95.0).unwrap(), 39.0);
+        assert_eq!(extended.y_at_x(120.0).unwrap(), 54.0);
+        assert!(extended.y_at_x(120.5).is_err());
+
+        let one_sided = table
+            .clone()
+            .with_guards(None, Some((101.0, Extend)))
+            .unwrap();
+        assert_eq!(one_sided.domain(), (0.0, 101.0));
+        assert_eq!(table.clone().with_guards(None, None).unwrap(), table);
+
+        let logy = crate::tests::table(&[(1.0, 1.0), (2.0, 4.0)])
+            .with_interp(InterpKind::LogY)
+            .unwrap()
+            .with_guards(Some((0.0, Hold)), Some((3.0, Extend)))
+            .unwrap();
+        assert_eq!(
+            logy.segment_kinds(),
+            [InterpKind::Linear, InterpKind::LogY, InterpKind::LogY]
+        );
+        assert!(ulps_apart(logy.y_at_x(3.0).unwrap(), 16.0) <= 4);
+    }
+
+    #[test]
+    fn with_guards_errors() {
+        let table = Piecewise::try_from(Vec::from(SIDEARM)).unwrap();
+        assert!(matches!(
+            table.clone().with_guards(Some((0.0, Hold)), None),
+            Err(PiecewiseErr::GuardInsideDomain {
+                x: 0.0,
+                min: 0.0,
+                max: 100.0
+            })
+        ));
+        assert_eq!(
+            table
+                .clone()
+                .with_guards(Some((-1.0, Hold)), Some((50.0, Extend)))
+                .unwrap_err()
+                .to_string(),
+            "A guard at x = 50 is not outside the domain [0, 100]"
+        );
+        assert!(matches!(
+            table.with_guards(None, Some((f64::NAN, Hold))),
+            Err(PiecewiseErr::InputNaN(_))
+        ));
+    }
+}
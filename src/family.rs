@@ -0,0 +1,243 @@
+use serde::{de, Deserialize, Deserializer, Serialize, Serializer};
+
+use crate::{Piecewise, PiecewiseErr};
+
+/// What a [`PiecewiseFamily`] does with a parameter outside the authored ones.
+#[derive(Debug, Clone, Copy, Default, Hash, PartialEq, Eq, Serialize, Deserialize)]
+pub enum ParameterPolicy {
+    /// Error with [`PiecewiseErr::NotInDomain`].
+    #[default]
+    Error,
+    /// Use the curve of the nearest authored parameter.
+    Clamp,
+}
+
+/// Tables authored at a few values of some parameter, morphed between for the values
+/// in between.
+///
+/// Between two authored parameters the curve is the [`Piecewise::blend`] of the two
+/// authored tables around it, and at an authored parameter it's exactly that table.
+///
+/// Serializes as `{"curves": [[parameter, table], ...], "policy": "Error"}`, where the
+/// policy can be left out.
+#[derive(Debug, Clone, PartialEq)]
+pub struct PiecewiseFamily {
+    curves: Vec<(f64, Piecewise)>,
+    policy: ParameterPolicy,
+}
+
+impl TryFrom<Vec<(f64, Piecewise)>> for PiecewiseFamily {
+    type Error = PiecewiseErr;
+    /// The parameters have to be finite and strictly increasing, and the tables linear
+    /// like [`Piecewise::blend`] needs.
+    fn try_from(curves: Vec<(f64, Piecewise)>) -> Result<Self, Self::Error> {
+        if curves.is_empty() {
+            return Err(PiecewiseErr::InputEmpty);
+        }
+        for (index, (parameter, table)) in curves.iter().enumerate() {
+            let parameter = *parameter;
+            table.check_linear()?;
+            if !parameter.is_finite() {
+                return Err(PiecewiseErr::NonFiniteParameter { index });
+            }
+            if index > 0 && parameter <= curves[index - 1].0 {
+                return Err(PiecewiseErr::KnotsNotIncreasing { index });
+            }
+        }
+        Ok(PiecewiseFamily {
+            curves,
+            policy: ParameterPolicy::default(),
+        })
+    }
+}
+
+impl PiecewiseFamily {
+    /// The same family handling out of range parameters with `policy`.
+    pub fn with_policy(mut self, policy: ParameterPolicy) -> PiecewiseFamily {
+        self.policy = policy;
+        self
+    }
+
+    /// The curve at parameter `p`.
+    pub fn curve_at(&self, p: f64) -> Result<Piecewise, PiecewiseErr> {
+        match self.bracket(p)? {
+            (low, None) => Ok(low.clone()),
+            (low, Some((high, t))) => low.blend(high, t),
+        }
+    }
+
+    /// The curve at parameter `p` evaluated at `x`, without building the curve. This is
+    /// always what evaluating [`PiecewiseFamily::curve_at`] gives.
+    ///
+    /// Between authored parameters `x` has to be in the domain of both tables around
+    /// `p`, like it has to be in the domain of their blend.
+    pub fn eval(&self, p: f64, x: f64) -> Result<f64, PiecewiseErr> {
+        match self.bracket(p)? {
+            (low, None) => low.y_at_x(x),
+            (low, Some((high, t))) => Ok((1.0 - t) * low.y_at_x(x)? + t * high.y_at_x(x)?),
+        }
+    }
+
+    pub fn policy(&self) -> ParameterPolicy {
+        self.policy
+    }
+
+    /// The authored parameters and tables, by increasing parameter.
+    pub fn curves(&self) -> &[(f64, Piecewise)] {
+        &self.curves
+    }
+
+    /// The authored table at or just below `p`, and the one above it together with how
+    /// far `p` is towards it, unless `p` lands on an authored table.
+    fn bracket(&self, p: f64) -> Result<Bracket<'_>, PiecewiseErr> {
+        if p.is_nan() {
+            return Err(PiecewiseErr::InputNaN(ordered_float::FloatIsNan));
+        }
+        let (first, last) = (&self.curves[0], &self.curves[self.curves.len() - 1]);
+        if p < first.0 || p > last.0 {
+            return match self.policy {
+                ParameterPolicy::Error => Err(PiecewiseErr::NotInDomain),
+                ParameterPolicy::Clamp if p < first.0 => Ok((&first.1, None)),
+                ParameterPolicy::Clamp => Ok((&last.1, None)),
+            };
+        }
+
+        let index = self
+            .curves
+            .partition_point(|&(parameter, _)| parameter <= p);
+        let (low_p, low) = &self.curves[index - 1];
+        if *low_p == p {
+            return Ok((low, None));
+        }
+        let (high_p, high) = &self.curves[index];
+        Ok((low, Some((high, (p - low_p) / (high_p - low_p)))))
+    }
+}
+
+type Bracket<'a> = (&'a Piecewise, Option<(&'a Piecewise, f64)>);
+
+#[derive(Serialize, Deserialize)]
+struct FamilyRepr<T> {
+    curves: T,
+    #[serde(default)]
+    policy: ParameterPolicy,
+}
+
+impl Serialize for PiecewiseFamily {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        FamilyRepr {
+            curves: &self.curves,
+            policy: self.policy,
+        }
+        .serialize(serializer)
+    }
+}
+
+impl<'de> Deserialize<'de> for PiecewiseFamily {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let FamilyRepr { curves, policy } =
+            FamilyRepr::<Vec<(f64, Piecewise)>>::deserialize(deserializer)?;
+        let family = PiecewiseFamily::try_from(curves).map_err(de::Error::custom)?;
+        Ok(family.with_policy(policy))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{ParameterPolicy, PiecewiseFamily};
+    use crate::tests::{table, SIDEARM};
+    use crate::{InterpKind, Piecewise, PiecewiseErr};
+
+    fn barrels() -> PiecewiseFamily {
+        PiecewiseFamily::try_from(vec![
+            (10.0, Piecewise::try_from(Vec::from(SIDEARM)).unwrap()),
+            (20.0, table(&[(0.0, 20.0), (50.0, 20.0), (100.0, 20.0)])),
+            (30.0, table(&[(0.0, 30.0), (100.0, 10.0)])),
+        ])
+        .unwrap()
+    }
+
+    #[test]
+    fn authored_and_between() {
+        let family = barrels();
+        assert_eq!(&family.curve_at(20.0).unwrap(), &family.curves()[1].1);
+        assert_eq!(family.curve_at(10.0).unwrap().as_slice(), SIDEARM);
+        assert_eq!(family.eval(10.0, 95.0).unwrap(), 39.0);
+
+        let halfway = family.curve_at(15.0).unwrap();
+        assert_eq!(
+            halfway,
+            table(&[(0.0, 19.0), (50.0, 24.0), (90.0, 28.0), (100.0, 31.0)])
+        );
+        for x in [0.0, 25.0, 90.0, 95.0] {
+            assert_eq!(family.eval(15.0, x).unwrap(), halfway.y_at_x(x).unwrap());
+        }
+        assert_eq!(family.eval(25.0, 50.0).unwrap(), 20.0);
+        for p in [10.0, 12.5, 15.0, 21.0, 29.75] {
+            let curve = family.curve_at(p).unwrap();
+            for x in [0.0, 10.0, 49.5, 50.0, 77.0, 90.0, 99.0, 100.0] {
+                let (y, eval) = (curve.y_at_x(x).unwrap(), family.eval(p, x).unwrap());
+                assert!((y - eval).abs() < 1e-12, "{p}, {x}: {y} != {eval}");
+            }
+        }
+    }
+
+    #[test]
+    fn out_of_range() {
+        let family = barrels();
+        assert!(matches!(
+            family.curve_at(5.0),
+            Err(PiecewiseErr::NotInDomain)
+        ));
+        assert!(family.eval(31.0, 0.0).is_err());
+        assert!(family.eval(f64::NAN, 0.0).is_err());
+
+        let clamped = family.with_policy(ParameterPolicy::Clamp);
+        assert_eq!(clamped.curve_at(5.0).unwrap().as_slice(), SIDEARM);
+        assert_eq!(clamped.eval(100.0, 0.0).unwrap(), 30.0);
+    }
+
+    #[test]
+    fn validation_and_serde() {
+        let sidearm = Piecewise::try_from(Vec::from(SIDEARM)).unwrap();
+        assert!(matches!(
+            PiecewiseFamily::try_from(vec![(1.0, sidearm.clone()), (1.0, sidearm.clone())]),
+            Err(PiecewiseErr::KnotsNotIncreasing { index: 1 })
+        ));
+        assert!(matches!(
+            PiecewiseFamily::try_from(vec![(f64::INFINITY, sidearm.clone())]),
+            Err(PiecewiseErr::NonFiniteParameter { index: 0 })
+        ));
+        assert!(PiecewiseFamily::try_from(vec![]).is_err());
+        let logy = table(&[(1.0, 1.0), (2.0, 100.0)])
+            .with_interp(InterpKind::LogY)
+            .unwrap();
+        assert!(matches!(
+            PiecewiseFamily::try_from(vec![(0.0, logy.clone()), (1.0, logy.clone())]),
+            Err(PiecewiseErr::UnsupportedInterp(InterpKind::LogY))
+        ));
+        assert!(matches!(
+            logy.blend(&logy, 0.5),
+            Err(PiecewiseErr::UnsupportedInterp(InterpKind::LogY))
+        ));
+
+        let family = barrels().with_policy(ParameterPolicy::Clamp);
+        let json = serde_json::to_string(&family).unwrap();
+        assert!(
+            json.starts_with(r#"{"curves":[[10.0,[[0.0,18.0],"#),
+            "{json}"
+        );
+        assert!(json.ends_with(r#""policy":"Clamp"}"#), "{json}");
+        let back: PiecewiseFamily = serde_json::from_str(&json).unwrap();
+        assert_eq!(back, family);
+
+        let defaulted: PiecewiseFamily =
+            serde_json::from_str(r#"{"curves": [[1, [[0, 1]]], [2, [[0, 2]]]]}"#).unwrap();
+        assert_eq!(defaulted.policy(), ParameterPolicy::Error);
+        assert_eq!(defaulted.eval(1.5, 0.0).unwrap(), 1.5);
+        assert!(serde_json::from_str::<PiecewiseFamily>(
+            r#"{"curves": [[2, [[0, 1]]], [1, [[0, 2]]]]}"#
+        )
+        .is_err());
+    }
+}
@@ -0,0 +1,211 @@
+//! A C interface, behind the `ffi` feature.
+//!
+//! Every function returns one of the `LERP_TABLE_*` codes, with [`LERP_TABLE_OK`] on
+//! success and one code per [`PiecewiseErr`] variant otherwise. Null pointers are
+//! reported with [`LERP_TABLE_NULL_POINTER`] rather than dereferenced.
+
+use std::ptr;
+
+use crate::{Coord, Piecewise, PiecewiseErr};
+
+/// An opaque handle to a table, created by [`lerp_table_new`] and released by
+/// [`lerp_table_free`].
+pub struct LerpTable(Piecewise);
+
+pub const LERP_TABLE_OK: i32 = 0;
+pub const LERP_TABLE_NULL_POINTER: i32 = 1;
+pub const LERP_TABLE_INPUT_EMPTY: i32 = 2;
+pub const LERP_TABLE_INPUT_UNDEFINED: i32 = 3;
+pub const LERP_TABLE_NOT_IN_DOMAIN: i32 = 4;
+pub const LERP_TABLE_KNOTS_NOT_INCREASING: i32 = 5;
+pub const LERP_TABLE_UNDERDETERMINED: i32 = 6;
+pub const LERP_TABLE_NOT_POSITIVE: i32 = 7;
+pub const LERP_TABLE_INDEX_OUT_OF_RANGE: i32 = 8;
+pub const LERP_TABLE_NOT_A_LEVEL: i32 = 9;
+pub const LERP_TABLE_UNSUPPORTED_INTERP: i32 = 10;
+pub const LERP_TABLE_INVALID_PERIOD: i32 = 11;
+pub const LERP_TABLE_INVALID_RESOLUTION: i32 = 12;
+pub const LERP_TABLE_DISJOINT_DOMAINS: i32 = 13;
+pub const LERP_TABLE_LENGTH_MISMATCH: i32 = 14;
+pub const LERP_TABLE_INPUT_NAN: i32 = 15;
+pub const LERP_TABLE_INVALID_WINDOW: i32 = 16;
+pub const LERP_TABLE_INVALID_GRID: i32 = 17;
+pub const LERP_TABLE_INPUT_NAN_AT: i32 = 18;
+pub const LERP_TABLE_NON_FINITE_RESULT: i32 = 19;
+pub const LERP_TABLE_BAND_CROSSED: i32 = 20;
+pub const LERP_TABLE_NON_FINITE_PARAMETER: i32 = 21;
+pub const LERP_TABLE_INVALID_INTERVAL: i32 = 22;
+pub const LERP_TABLE_DEGENERATE_DOMAIN: i32 = 23;
+pub const LERP_TABLE_NON_FINITE_KNOT: i32 = 24;
+pub const LERP_TABLE_GUARD_INSIDE_DOMAIN: i32 = 25;
+
+fn error_code(err: &PiecewiseErr) -> i32 {
+    match err {
+        PiecewiseErr::InputEmpty => LERP_TABLE_INPUT_EMPTY,
+        PiecewiseErr::InputUndefined { .. } => LERP_TABLE_INPUT_UNDEFINED,
+        PiecewiseErr::NotInDomain => LERP_TABLE_NOT_IN_DOMAIN,
+        PiecewiseErr::KnotsNotIncreasing { .. } => LERP_TABLE_KNOTS_NOT_INCREASING,
+        PiecewiseErr::Underdetermined { .. } => LERP_TABLE_UNDERDETERMINED,
+        PiecewiseErr::NotPositive { .. } => LERP_TABLE_NOT_POSITIVE,
+        PiecewiseErr::IndexOutOfRange { .. } => LERP_TABLE_INDEX_OUT_OF_RANGE,
+        PiecewiseErr::NotALevel { .. } => LERP_TABLE_NOT_A_LEVEL,
+        PiecewiseErr::UnsupportedInterp(_) => LERP_TABLE_UNSUPPORTED_INTERP,
+        PiecewiseErr::InvalidPeriod(_) => LERP_TABLE_INVALID_PERIOD,
+        PiecewiseErr::InvalidResolution(_) => LERP_TABLE_INVALID_RESOLUTION,
+        PiecewiseErr::InvalidWindow(_) => LERP_TABLE_INVALID_WINDOW,
+        PiecewiseErr::InvalidGrid(_) => LERP_TABLE_INVALID_GRID,
+        PiecewiseErr::DisjointDomains => LERP_TABLE_DISJOINT_DOMAINS,
+        PiecewiseErr::LengthMismatch { .. } => LERP_TABLE_LENGTH_MISMATCH,
+        PiecewiseErr::InputNaN(_) => LERP_TABLE_INPUT_NAN,
+        PiecewiseErr::InputNaNAt { .. } => LERP_TABLE_INPUT_NAN_AT,
+        PiecewiseErr::NonFiniteResult { .. } => LERP_TABLE_NON_FINITE_RESULT,
+        PiecewiseErr::BandCrossed { .. } => LERP_TABLE_BAND_CROSSED,
+        PiecewiseErr::NonFiniteParameter { .. } => LERP_TABLE_NON_FINITE_PARAMETER,
+        PiecewiseErr::InvalidInterval { .. } => LERP_TABLE_INVALID_INTERVAL,
+        PiecewiseErr::DegenerateDomain { .. } => LERP_TABLE_DEGENERATE_DOMAIN,
+        PiecewiseErr::NonFiniteKnot { .. } => LERP_TABLE_NON_FINITE_KNOT,
+        PiecewiseErr::GuardInsideDomain { .. } => LERP_TABLE_GUARD_INSIDE_DOMAIN,
+    }
+}
+
+/// Builds a table from `len` x and y values and writes its handle to `out`.
+///
+/// On error `out` is set to null.
+///
+/// # Safety
+/// `out` must be null or valid for a write. Unless `len` is 0, `xs` and `ys` must be
+/// null or point to `len` readable doubles each.
+#[no_mangle]
+pub unsafe extern "C" fn lerp_table_new(
+    xs: *const f64,
+    ys: *const f64,
+    len: usize,
+    out: *mut *mut LerpTable,
+) -> i32 {
+    if out.is_null() {
+        return LERP_TABLE_NULL_POINTER;
+    }
+    *out = ptr::null_mut();
+    if len == 0 {
+        return LERP_TABLE_INPUT_EMPTY;
+    }
+    if xs.is_null() || ys.is_null() {
+        return LERP_TABLE_NULL_POINTER;
+    }
+
+    let xs = std::slice::from_raw_parts(xs, len);
+    let ys = std::slice::from_raw_parts(ys, len);
+    let table = xs
+        .iter()
+        .zip(ys)
+        .map(|(&x, &y)| Coord::try_from((x, y)).map_err(PiecewiseErr::from))
+        .collect::<Result<Vec<_>, _>>()
+        .and_then(Piecewise::try_from);
+    match table {
+        Ok(table) => {
+            *out = Box::into_raw(Box::new(LerpTable(table)));
+            LERP_TABLE_OK
+        }
+        Err(err) => error_code(&err),
+    }
+}
+
+/// Evaluates `table` at `x`, writing the result to `out`.
+///
+/// # Safety
+/// `table` must be null or a live handle from [`lerp_table_new`], and `out` null or
+/// valid for a write.
+#[no_mangle]
+pub unsafe extern "C" fn lerp_table_eval(table: *const LerpTable, x: f64, out: *mut f64) -> i32 {
+    let (Some(table), false) = (table.as_ref(), out.is_null()) else {
+        return LERP_TABLE_NULL_POINTER;
+    };
+    match table.0.y_at_x(x) {
+        Ok(y) => {
+            *out = y;
+            LERP_TABLE_OK
+        }
+        Err(err) => error_code(&err),
+    }
+}
+
+/// Releases a handle. Null is ignored, like `free`.
+///
+/// # Safety
+/// `table` must be null or a live handle from [`lerp_table_new`]. Like `free`, a
+/// handle can't be released twice.
+#[no_mangle]
+pub unsafe extern "C" fn lerp_table_free(table: *mut LerpTable) {
+    if !table.is_null() {
+        drop(Box::from_raw(table));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::ptr;
+
+    use super::*;
+
+    #[test]
+    fn round_trip() {
+        let (xs, ys) = ([90.0, 0.0, 100.0], [36.0, 18.0, 42.0]);
+        let mut table = ptr::null_mut();
+        let mut y = 0.0;
+        unsafe {
+            assert_eq!(
+                lerp_table_new(xs.as_ptr(), ys.as_ptr(), 3, &mut table),
+                LERP_TABLE_OK
+            );
+            assert_eq!(lerp_table_eval(table, 95.0, &mut y), LERP_TABLE_OK);
+            assert_eq!(y, 39.0);
+            assert_eq!(
+                lerp_table_eval(table, 101.0, &mut y),
+                LERP_TABLE_NOT_IN_DOMAIN
+            );
+            assert_eq!(
+                lerp_table_eval(table, f64::NAN, &mut y),
+                LERP_TABLE_INPUT_NAN
+            );
+            assert_eq!(y, 39.0);
+            lerp_table_free(table);
+        }
+    }
+
+    #[test]
+    fn bad_input() {
+        let mut table = ptr::null_mut();
+        let mut y = 0.0;
+        unsafe {
+            let (xs, ys) = ([0.0, 0.0], [1.0, 2.0]);
+            assert_eq!(
+                lerp_table_new(xs.as_ptr(), ys.as_ptr(), 2, &mut table),
+                LERP_TABLE_INPUT_UNDEFINED
+            );
+            assert!(table.is_null());
+            let (xs, ys) = ([0.0, f64::NAN], [1.0, 2.0]);
+            assert_eq!(
+                lerp_table_new(xs.as_ptr(), ys.as_ptr(), 2, &mut table),
+                LERP_TABLE_INPUT_NAN
+            );
+
+            assert_eq!(
+                lerp_table_new(ptr::null(), ptr::null(), 0, &mut table),
+                LERP_TABLE_INPUT_EMPTY
+            );
+            assert_eq!(
+                lerp_table_new(ptr::null(), ys.as_ptr(), 2, &mut table),
+                LERP_TABLE_NULL_POINTER
+            );
+            assert_eq!(
+                lerp_table_new(xs.as_ptr(), ys.as_ptr(), 2, ptr::null_mut()),
+                LERP_TABLE_NULL_POINTER
+            );
+            assert_eq!(
+                lerp_table_eval(ptr::null(), 1.0, &mut y),
+                LERP_TABLE_NULL_POINTER
+            );
+            lerp_table_free(ptr::null_mut());
+        }
+    }
+}
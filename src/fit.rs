@@ -0,0 +1,702 @@
+use std::sync::Arc;
+
+use ordered_float::NotNan;
+use serde::Serialize;
+
+use crate::{Coord, Piecewise, PiecewiseErr};
+
+/// Which way a table is forced to go by [`Piecewise::make_monotonic`].
+#[derive(Debug, Clone, Copy, Hash, PartialEq, Eq, Serialize)]
+pub enum Monotonicity {
+    NonDecreasing,
+    NonIncreasing,
+}
+
+impl Piecewise {
+    /// Approximates `samples` with a table of at most `max_knots` knots.
+    ///
+    /// The error minimized is L2. Knots are chosen greedily: starting from the extreme
+    /// x values of the samples, the segment with the largest squared residual is split
+    /// at the sample nearest its middle until the budget is spent. The knot y values
+    /// are the least squares fit for the chosen positions, so they generally won't
+    /// pass through any sample.
+    ///
+    /// NaN samples are rejected, repeated x values are fine since they're just more
+    /// data. Both endpoints are kept, so `max_knots` has to be at least 2 unless every
+    /// sample shares one x, and errors with [`PiecewiseErr::InvalidResolution`]
+    /// otherwise.
+    pub fn fit(samples: &[(f64, f64)], max_knots: usize) -> Result<Piecewise, PiecewiseErr> {
+        let samples = checked_samples(samples)?;
+        let min_x = samples[0].0;
+        let max_x = samples[samples.len() - 1].0;
+
+        if min_x == max_x {
+            let mean = samples.iter().map(|s| s.1).sum::<f64>() / samples.len() as f64;
+            if max_knots == 0 {
+                return Err(PiecewiseErr::InvalidResolution(max_knots));
+            }
+            return Piecewise::try_from(vec![Coord::try_from((min_x, mean))?]);
+        }
+        if max_knots < 2 {
+            return Err(PiecewiseErr::InvalidResolution(max_knots));
+        }
+
+        let mut knots = vec![min_x, max_x];
+        let mut ys = least_squares(&knots, &samples)?;
+        while knots.len() < max_knots {
+            //squared residual of every segment under the current fit
+            let mut errors = vec![0.0; knots.len() - 1];
+            for &(x, y) in &samples {
+                errors[segment(&knots, x)] += (y - evaluate(&knots, &ys, x)).powi(2);
+            }
+
+            //split the worst segment that still has a sample strictly inside it,
+            //at the sample closest to its midpoint
+            let split = (0..errors.len())
+                .filter(|&i| errors[i] > 0.0)
+                .filter_map(|i| {
+                    let (left, right) = (knots[i], knots[i + 1]);
+                    let middle = left + (right - left) / 2.0;
+                    samples
+                        .iter()
+                        .map(|s| s.0)
+                        .filter(|&x| x > left && x < right)
+                        .min_by(|a, b| (a - middle).abs().total_cmp(&(b - middle).abs()))
+                        .map(|x| (errors[i], x))
+                })
+                .max_by(|a, b| a.0.total_cmp(&b.0));
+
+            match split {
+                Some((_, x)) => {
+                    let index = knots.partition_point(|&k| k < x);
+                    knots.insert(index, x);
+                }
+                None => break,
+            }
+            ys = least_squares(&knots, &samples)?;
+        }
+
+        let points = knots
+            .into_iter()
+            .zip(ys)
+            .map(Coord::try_from)
+            .collect::<Result<Vec<_>, _>>()?;
+        Piecewise::try_from(points)
+    }
+
+    /// Finds the y values at the given knot positions that best fit `samples`
+    /// in the least squares sense.
+    ///
+    /// `knot_xs` must be strictly increasing, every sample must lie within them,
+    /// and every interval between neighbouring knots needs at least one sample.
+    pub fn fit_at_knots(
+        knot_xs: &[f64],
+        samples: &[(f64, f64)],
+    ) -> Result<Piecewise, PiecewiseErr> {
+        let samples = checked_samples(samples)?;
+        if knot_xs.is_empty() {
+            return Err(PiecewiseErr::InputEmpty);
+        }
+        for (index, &x) in knot_xs.iter().enumerate() {
+            NotNan::new(x)?;
+            if index > 0 && x <= knot_xs[index - 1] {
+                return Err(PiecewiseErr::KnotsNotIncreasing { index });
+            }
+        }
+
+        let (min_x, max_x) = (knot_xs[0], knot_xs[knot_xs.len() - 1]);
+        if samples[0].0 < min_x || samples[samples.len() - 1].0 > max_x {
+            return Err(PiecewiseErr::NotInDomain);
+        }
+        if knot_xs.len() == 1 {
+            let mean = samples.iter().map(|s| s.1).sum::<f64>() / samples.len() as f64;
+            return Piecewise::try_from(vec![Coord::try_from((min_x, mean))?]);
+        }
+
+        for pair in knot_xs.windows(2) {
+            let (left, right) = (pair[0], pair[1]);
+            let start = samples.partition_point(|s| s.0 < left);
+            if !samples.get(start).is_some_and(|s| s.0 <= right) {
+                return Err(PiecewiseErr::Underdetermined { left, right });
+            }
+        }
+
+        let ys = least_squares(knot_xs, &samples)?;
+        let points = knot_xs
+            .iter()
+            .copied()
+            .zip(ys)
+            .map(Coord::try_from)
+            .collect::<Result<Vec<_>, _>>()?;
+        Piecewise::try_from(points)
+    }
+
+    /// A linear table through this one evaluated at `xs`.
+    ///
+    /// `xs` can be in any order and repeat values. Every x has to be within the domain,
+    /// and a NaN errors with [`PiecewiseErr::InputNaNAt`] giving its index in `xs`.
+    /// Sampling a linear table at its own knot x values gives back the same table.
+    pub fn resample_at(&self, xs: &[f64]) -> Result<Piecewise, PiecewiseErr> {
+        if let Some(index) = xs.iter().position(|x| x.is_nan()) {
+            return Err(PiecewiseErr::InputNaNAt { index });
+        }
+        let mut xs = xs.to_vec();
+        xs.sort_by(f64::total_cmp);
+        xs.dedup();
+
+        let points = xs
+            .into_iter()
+            .map(|x| Ok(Coord::try_from((x, self.y_at_x(x)?))?))
+            .collect::<Result<Vec<_>, PiecewiseErr>>()?;
+        Piecewise::try_from(points)
+    }
+
+    /// The closest table, in least squares over the knot y values, that goes in
+    /// `direction`. The x values and segment kinds are kept.
+    ///
+    /// This is the pool adjacent violators algorithm: runs of knots that go the wrong
+    /// way are replaced by their mean. Repeated knots count once per repeat and always
+    /// stay together. A table that is already monotonic comes back unchanged. An
+    /// infinite y that has to be pooled with one of the opposite sign has no mean, so
+    /// the two meet at 0.
+    pub fn make_monotonic(&self, direction: Monotonicity) -> Piecewise {
+        //non-increasing is non-decreasing upside down
+        let sign = match direction {
+            Monotonicity::NonDecreasing => 1.0,
+            Monotonicity::NonIncreasing => -1.0,
+        };
+        let data = self.as_slice();
+
+        //(mean, weight, knot count) of each pool. Means are only recomputed on a merge,
+        //so untouched knots keep their exact y
+        let mut pools: Vec<(NotNan<f64>, f64, usize)> = Vec::new();
+        for repeats in data.chunk_by(|a, b| a.0 == b.0) {
+            let count = repeats.len();
+            let mut pool = (repeats[0].1 * sign, count as f64, count);
+            while let Some(&last) = pools.last() {
+                if last.0 <= pool.0 {
+                    break;
+                }
+                pools.pop();
+                let weight = last.1 + pool.1;
+                //weighting each mean first can't overflow, unlike summing them
+                let mean = last.0.into_inner() * (last.1 / weight)
+                    + pool.0.into_inner() * (pool.1 / weight);
+                pool = (
+                    NotNan::new(mean).unwrap_or_default(),
+                    weight,
+                    last.2 + pool.2,
+                );
+            }
+            pools.push(pool);
+        }
+
+        let ys = pools
+            .iter()
+            .flat_map(|&(mean, _, count)| std::iter::repeat_n(mean * sign, count));
+        let points = data
+            .iter()
+            .zip(ys)
+            .map(|(point, y)| Coord(point.0, y))
+            .collect();
+        Piecewise(Arc::new(points), Arc::clone(&self.1))
+    }
+
+    /// Replaces each knot's y with the mean of the `window` knots centered on it,
+    /// keeping the x values and segment kinds.
+    ///
+    /// Near the ends the window shrinks evenly on both sides instead of padding, so
+    /// the first and last knots keep their y exactly. Repeated knots count once.
+    /// `window` has to be odd, and 1 returns the table unchanged.
+    pub fn smooth(&self, window: usize) -> Result<Piecewise, PiecewiseErr> {
+        if window.is_multiple_of(2) {
+            return Err(PiecewiseErr::InvalidWindow(window));
+        }
+        let data = self.as_slice();
+        let ys = data
+            .chunk_by(|a, b| a.0 == b.0)
+            .map(|repeats| (repeats.len(), repeats[0].y()))
+            .collect::<Vec<_>>();
+
+        let last = ys.len() - 1;
+        let smoothed = (0..ys.len()).flat_map(|i| {
+            let half = (window / 2).min(i).min(last - i);
+            let sum: f64 = ys[i - half..=i + half].iter().map(|&(_, y)| y).sum();
+            std::iter::repeat_n(sum / (2 * half + 1) as f64, ys[i].0)
+        });
+        let points = data
+            .iter()
+            .zip(smoothed)
+            .map(|(point, y)| Ok(Coord(point.0, NotNan::new(y)?)))
+            .collect::<Result<Vec<_>, PiecewiseErr>>()?;
+        Ok(Piecewise(Arc::new(points), Arc::clone(&self.1)))
+    }
+
+    /// Removes knots while keeping the table within `tolerance` of the original in y
+    /// at every x.
+    ///
+    /// This is Douglas-Peucker on vertical distance: a run of knots is replaced by a
+    /// single segment unless one of them is further than `tolerance` from it, in which
+    /// case the furthest is kept and both halves are tried again. The first and last
+    /// knots always stay. Only linear tables can be simplified, since the bound relies
+    /// on the removed parts being straight.
+    pub fn simplify(&self, tolerance: f64) -> Result<Piecewise, PiecewiseErr> {
+        self.simplify_keeping(tolerance, &[])
+    }
+
+    /// [`Piecewise::simplify`], but the knots at the indices in `keep` are never removed.
+    ///
+    /// The bound still holds for every removed knot, and an index past the last knot
+    /// errors with [`PiecewiseErr::IndexOutOfRange`].
+    pub fn simplify_keeping(
+        &self,
+        tolerance: f64,
+        keep: &[usize],
+    ) -> Result<Piecewise, PiecewiseErr> {
+        let tolerance = NotNan::new(tolerance)?.into_inner();
+        self.check_linear()?;
+        let data = self.as_slice();
+        let len = data.len();
+        let mut kept = vec![false; len];
+        kept[0] = true;
+        kept[len - 1] = true;
+        for &index in keep {
+            if index >= len {
+                return Err(PiecewiseErr::IndexOutOfRange { index, len });
+            }
+            kept[index] = true;
+        }
+
+        //the kept knots split the table into runs that are simplified independently
+        let anchors = (0..len).filter(|&i| kept[i]).collect::<Vec<_>>();
+        let mut runs = anchors
+            .windows(2)
+            .map(|pair| (pair[0], pair[1]))
+            .collect::<Vec<_>>();
+        while let Some((first, last)) = runs.pop() {
+            let furthest = (first + 1..last)
+                .map(|i| (i, deviation(data[first], data[last], data[i])))
+                .max_by(|a, b| a.1.total_cmp(&b.1));
+            match furthest {
+                //knots next to infinities have no meaningful distance and are kept
+                Some((i, error)) if error > tolerance || error.is_nan() => {
+                    kept[i] = true;
+                    runs.push((first, i));
+                    runs.push((i, last));
+                }
+                _ => (),
+            }
+        }
+
+        let mut kept = kept.into_iter();
+        self.filtered(|_| kept.next().unwrap_or(true))
+    }
+}
+
+/// How far `point` is in y from the segment between `left` and `right`.
+fn deviation(left: Coord, right: Coord, point: Coord) -> f64 {
+    if left.0 == right.0 {
+        return (point.y() - left.y()).abs();
+    }
+    let t = (point.x() - left.x()) / (right.x() - left.x());
+    (left.y() + t * (right.y() - left.y()) - point.y()).abs()
+}
+
+/// Rejects empty or NaN input, returning the samples sorted by x.
+fn checked_samples(samples: &[(f64, f64)]) -> Result<Vec<(f64, f64)>, PiecewiseErr> {
+    if samples.is_empty() {
+        return Err(PiecewiseErr::InputEmpty);
+    }
+    for &(x, y) in samples {
+        NotNan::new(x)?;
+        NotNan::new(y)?;
+    }
+    let mut samples = samples.to_vec();
+    samples.sort_by(|a, b| a.0.total_cmp(&b.0));
+    Ok(samples)
+}
+
+/// The segment of the sorted `knots` containing `x`, as the index of its left knot.
+fn segment(knots: &[f64], x: f64) -> usize {
+    knots.partition_point(|&k| k <= x).clamp(1, knots.len() - 1) - 1
+}
+
+fn evaluate(knots: &[f64], ys: &[f64], x: f64) -> f64 {
+    let i = segment(knots, x);
+    let t = (x - knots[i]) / (knots[i + 1] - knots[i]);
+    ys[i] + t * (ys[i + 1] - ys[i])
+}
+
+/// Solves for the knot y values minimizing the squared error against `samples`.
+///
+/// The basis functions are the piecewise linear hats centered on each knot, which
+/// makes the normal equations tridiagonal. `knots` must be sorted and distinct,
+/// and every sample must lie within them.
+pub(crate) fn least_squares(
+    knots: &[f64],
+    samples: &[(f64, f64)],
+) -> Result<Vec<f64>, PiecewiseErr> {
+    let n = knots.len();
+    let mut diagonal = vec![0.0; n];
+    let mut upper = vec![0.0; n - 1];
+    let mut rhs = vec![0.0; n];
+
+    for &(x, y) in samples {
+        let i = segment(knots, x);
+        let t = (x - knots[i]) / (knots[i + 1] - knots[i]);
+        let (left, right) = (1.0 - t, t);
+        diagonal[i] += left * left;
+        diagonal[i + 1] += right * right;
+        upper[i] += left * right;
+        rhs[i] += left * y;
+        rhs[i + 1] += right * y;
+    }
+
+    //a knot with no weight behind it can be anything, so there's no unique answer
+    let underdetermined = |i: usize| {
+        let left = knots[i.saturating_sub(1)];
+        let right = knots[(i + 1).min(n - 1)];
+        PiecewiseErr::Underdetermined { left, right }
+    };
+
+    //Thomas algorithm, the system is symmetric so the lower diagonal is `upper` too
+    for i in 1..n {
+        if diagonal[i - 1] <= 0.0 {
+            return Err(underdetermined(i - 1));
+        }
+        let factor = upper[i - 1] / diagonal[i - 1];
+        diagonal[i] -= factor * upper[i - 1];
+        rhs[i] -= factor * rhs[i - 1];
+    }
+    if diagonal[n - 1] <= 0.0 {
+        return Err(underdetermined(n - 1));
+    }
+    let mut ys = vec![0.0; n];
+    ys[n - 1] = rhs[n - 1] / diagonal[n - 1];
+    for i in (0..n - 1).rev() {
+        ys[i] = (rhs[i] - upper[i] * ys[i + 1]) / diagonal[i];
+    }
+    Ok(ys)
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::tests::SIDEARM;
+    use crate::{Coord, InterpKind, Piecewise, PiecewiseErr};
+
+    fn rms_error(table: &Piecewise, samples: &[(f64, f64)]) -> f64 {
+        let sum: f64 = samples
+            .iter()
+            .map(|&(x, y)| (table.y_at_x(x).unwrap() - y).powi(2))
+            .sum();
+        (sum / samples.len() as f64).sqrt()
+    }
+
+    #[test]
+    fn fit_beats_uniform_resample() {
+        //a quadratic with deterministic pseudo random noise
+        let samples: Vec<(f64, f64)> = (0..1000)
+            .map(|i| {
+                let x = i as f64 / 100.0;
+                let noise = ((i * 7919) % 101) as f64 / 100.0 - 0.5;
+                (x, x * x + noise)
+            })
+            .collect();
+
+        let fitted = Piecewise::fit(&samples, 5).unwrap();
+        assert_eq!(fitted.as_slice().len(), 5);
+        assert_eq!(fitted.as_slice()[0].0, 0.0);
+        assert_eq!(fitted.as_slice()[4].0, 9.99);
+
+        let uniform: Vec<Coord> = [0, 250, 500, 750, 999]
+            .iter()
+            .map(|&i| Coord::try_from(samples[i]).unwrap())
+            .collect();
+        let uniform = Piecewise::try_from(uniform).unwrap();
+
+        assert!(rms_error(&fitted, &samples) < 0.75 * rms_error(&uniform, &samples));
+    }
+
+    #[test]
+    fn fit_rejects_bad_samples() {
+        assert!(Piecewise::fit(&[], 5).is_err());
+        assert!(Piecewise::fit(&[(0.0, 1.0), (f64::NAN, 2.0)], 5).is_err());
+
+        let single = Piecewise::fit(&[(1.0, 1.0), (1.0, 3.0)], 5).unwrap();
+        assert_eq!(single.y_at_x(1.0).unwrap(), 2.0);
+        assert_eq!(
+            Piecewise::fit(&[(1.0, 1.0), (1.0, 3.0)], 1).unwrap(),
+            single
+        );
+        assert!(matches!(
+            Piecewise::fit(&[(1.0, 1.0)], 0),
+            Err(PiecewiseErr::InvalidResolution(0))
+        ));
+
+        let line = [(0.0, 0.0), (1.0, 1.0), (2.0, 2.0)];
+        assert!(matches!(
+            Piecewise::fit(&line, 1),
+            Err(PiecewiseErr::InvalidResolution(1))
+        ));
+        assert_eq!(Piecewise::fit(&line, 2).unwrap().points().len(), 2);
+    }
+
+    #[test]
+    fn fit_at_knots_recovers_exact_data() {
+        let original = Piecewise::try_from(Vec::from(SIDEARM)).unwrap();
+        let samples: Vec<(f64, f64)> = (0..=40)
+            .map(|i| {
+                let x = i as f64 * 2.5;
+                (x, original.y_at_x(x).unwrap())
+            })
+            .collect();
+
+        let fitted = Piecewise::fit_at_knots(&[0.0, 90.0, 100.0], &samples).unwrap();
+        for (fit, exact) in fitted.as_slice().iter().zip(SIDEARM) {
+            assert_eq!(fit.0, exact.0);
+            assert!((fit.1 - exact.1).abs() < 1e-9);
+        }
+    }
+
+    #[test]
+    fn fit_at_knots_rejects_bad_knots() {
+        let samples = [(0.0, 0.0), (5.0, 1.0), (10.0, 2.0)];
+        assert!(matches!(
+            Piecewise::fit_at_knots(&[0.0, 10.0, 5.0], &samples),
+            Err(PiecewiseErr::KnotsNotIncreasing { index: 2 })
+        ));
+        assert!(matches!(
+            Piecewise::fit_at_knots(&[0.0, 5.0, 5.0, 10.0], &samples),
+            Err(PiecewiseErr::KnotsNotIncreasing { index: 2 })
+        ));
+        assert!(matches!(
+            Piecewise::fit_at_knots(&[0.0, f64::NAN], &samples),
+            Err(PiecewiseErr::InputNaN(_))
+        ));
+        assert!(matches!(
+            Piecewise::fit_at_knots(&[0.0, 6.0, 7.0, 10.0], &samples),
+            Err(PiecewiseErr::Underdetermined { left, right }) if left == 6.0 && right == 7.0
+        ));
+        assert!(matches!(
+            Piecewise::fit_at_knots(&[1.0, 10.0], &samples),
+            Err(PiecewiseErr::NotInDomain)
+        ));
+    }
+
+    #[test]
+    fn make_monotonic() {
+        use crate::tests::table;
+        use crate::Monotonicity::{NonDecreasing, NonIncreasing};
+
+        let noisy = table(&[
+            (0.0, 1.0),
+            (1.0, 3.0),
+            (2.0, 2.0),
+            (3.0, 4.0),
+            (4.0, 3.5),
+            (5.0, 5.0),
+        ]);
+        let expected = table(&[
+            (0.0, 1.0),
+            (1.0, 2.5),
+            (2.0, 2.5),
+            (3.0, 3.75),
+            (4.0, 3.75),
+            (5.0, 5.0),
+        ]);
+        assert_eq!(noisy.make_monotonic(NonDecreasing), expected);
+
+        //a violation that pools back through an earlier pool
+        let dip = table(&[(0.0, 1.0), (1.0, 3.0), (2.0, 3.0), (3.0, 0.0)]);
+        let pooled = table(&[(0.0, 1.0), (1.0, 2.0), (2.0, 2.0), (3.0, 2.0)]);
+        assert_eq!(dip.make_monotonic(NonDecreasing), pooled);
+        let flipped = table(&[(0.0, -1.0), (1.0, -3.0), (2.0, -3.0), (3.0, 0.0)]);
+        assert_eq!(
+            flipped.make_monotonic(NonIncreasing),
+            table(&[(0.0, -1.0), (1.0, -2.0), (2.0, -2.0), (3.0, -2.0)])
+        );
+
+        //repeated knots stay in step with each other
+        let repeated = table(&[(0.0, 5.0), (1.0, 3.0), (1.0, 3.0), (2.0, 9.0)]);
+        let result = repeated.make_monotonic(NonDecreasing);
+        assert_eq!(
+            result,
+            table(&[
+                (0.0, 11.0 / 3.0),
+                (1.0, 11.0 / 3.0),
+                (1.0, 11.0 / 3.0),
+                (2.0, 9.0)
+            ])
+        );
+
+        let huge = table(&[(0.0, f64::MAX), (1.0, -f64::MAX), (2.0, f64::MAX)]);
+        assert_eq!(
+            huge.make_monotonic(NonDecreasing),
+            table(&[(0.0, 0.0), (1.0, 0.0), (2.0, f64::MAX)])
+        );
+        let opposite = table(&[(0.0, f64::INFINITY), (1.0, f64::NEG_INFINITY)]);
+        assert_eq!(
+            opposite.make_monotonic(NonDecreasing),
+            table(&[(0.0, 0.0), (1.0, 0.0)])
+        );
+        assert_eq!(opposite.make_monotonic(NonIncreasing), opposite);
+    }
+
+    #[test]
+    fn make_monotonic_keeps_monotonic_tables() {
+        use crate::Monotonicity::{NonDecreasing, NonIncreasing};
+
+        let sidearm = Piecewise::try_from(Vec::from(SIDEARM)).unwrap();
+        let result = sidearm.make_monotonic(NonDecreasing);
+        assert_eq!(result, sidearm);
+        for (a, b) in result.as_slice().iter().zip(sidearm.as_slice()) {
+            assert_eq!(a.y().to_bits(), b.y().to_bits());
+        }
+
+        let falling = crate::tests::table(&[(0.0, 0.1 + 0.2), (1.0, -0.0), (2.0, -1e-300)]);
+        let result = falling.make_monotonic(NonIncreasing);
+        for (a, b) in result.as_slice().iter().zip(falling.as_slice()) {
+            assert_eq!(a.y().to_bits(), b.y().to_bits());
+        }
+    }
+
+    #[test]
+    fn smooth() {
+        use crate::tests::table;
+
+        let jagged = table(&[
+            (0.0, 0.0),
+            (1.0, 6.0),
+            (2.0, 3.0),
+            (3.0, 9.0),
+            (4.0, 3.0),
+            (5.0, 12.0),
+        ]);
+        let smoothed = jagged.smooth(3).unwrap();
+        let ys: Vec<f64> = smoothed.as_slice().iter().map(|point| point.y()).collect();
+        assert_eq!(ys, [0.0, 3.0, 6.0, 5.0, 8.0, 12.0]);
+
+        //the window shrinks to 3 next to the ends and 1 on them
+        let ys: Vec<f64> = jagged
+            .smooth(5)
+            .unwrap()
+            .as_slice()
+            .iter()
+            .map(|point| point.y())
+            .collect();
+        assert_eq!(ys, [0.0, 3.0, 4.2, 6.6, 8.0, 12.0]);
+
+        assert_eq!(jagged.smooth(1).unwrap(), jagged);
+        assert!(matches!(
+            jagged.smooth(0),
+            Err(PiecewiseErr::InvalidWindow(0))
+        ));
+        assert!(matches!(
+            jagged.smooth(4),
+            Err(PiecewiseErr::InvalidWindow(4))
+        ));
+
+        let repeated = table(&[(0.0, 0.0), (1.0, 3.0), (1.0, 3.0), (2.0, 9.0)]);
+        let smoothed = repeated.smooth(3).unwrap();
+        assert_eq!(
+            smoothed,
+            table(&[(0.0, 0.0), (1.0, 4.0), (1.0, 4.0), (2.0, 9.0)])
+        );
+        assert_eq!(
+            table(&[(1.0, 2.0)]).smooth(9).unwrap(),
+            table(&[(1.0, 2.0)])
+        );
+    }
+
+    #[test]
+    fn resample_at() {
+        let table = Piecewise::try_from(Vec::from(SIDEARM)).unwrap();
+        assert_eq!(table.resample_at(&[100.0, 0.0, 90.0, 0.0]).unwrap(), table);
+
+        let grid = [0.0, 25.0, 50.0, 75.0, 100.0];
+        let coarse = table.resample_at(&grid).unwrap();
+        assert_eq!(coarse.as_slice().len(), grid.len());
+        for x in grid {
+            assert_eq!(coarse.y_at_x(x).unwrap(), table.y_at_x(x).unwrap());
+        }
+        assert_eq!(coarse.domain(), (0.0, 100.0));
+        assert_eq!(table.resample_at(&[-0.0, 0.0]).unwrap().as_slice().len(), 1);
+    }
+
+    #[test]
+    fn resample_at_errors() {
+        let table = Piecewise::try_from(Vec::from(SIDEARM)).unwrap();
+        assert!(matches!(
+            table.resample_at(&[0.0, 50.0, f64::NAN]),
+            Err(PiecewiseErr::InputNaNAt { index: 2 })
+        ));
+        assert!(matches!(
+            table.resample_at(&[50.0, 101.0]),
+            Err(PiecewiseErr::NotInDomain)
+        ));
+        assert!(matches!(
+            table.resample_at(&[f64::INFINITY]),
+            Err(PiecewiseErr::NotInDomain)
+        ));
+        assert!(matches!(
+            table.resample_at(&[]),
+            Err(PiecewiseErr::InputEmpty)
+        ));
+    }
+
+    #[test]
+    fn simplify() {
+        let wobbly = crate::tests::table(&[
+            (0.0, 0.0),
+            (1.0, 1.001),
+            (2.0, 2.0),
+            (3.0, 2.9995),
+            (4.0, 4.0),
+            (5.0, 5.0),
+            (6.0, 3.0),
+        ]);
+        let simple = wobbly.simplify(0.01).unwrap();
+        assert_eq!(
+            simple,
+            crate::tests::table(&[(0.0, 0.0), (5.0, 5.0), (6.0, 3.0)])
+        );
+        assert_eq!(wobbly.simplify(0.0).unwrap(), wobbly);
+        assert_eq!(
+            Piecewise::try_from(Vec::from(SIDEARM))
+                .unwrap()
+                .simplify(100.0)
+                .unwrap()
+                .as_slice(),
+            [SIDEARM[0], SIDEARM[2]]
+        );
+
+        let kept = wobbly.simplify_keeping(0.01, &[2]).unwrap();
+        assert_eq!(
+            kept,
+            crate::tests::table(&[(0.0, 0.0), (2.0, 2.0), (5.0, 5.0), (6.0, 3.0)])
+        );
+        for table in [&simple, &kept] {
+            for point in wobbly.as_slice() {
+                let error = (table.y_at_x(point.x()).unwrap() - point.y()).abs();
+                assert!(error <= 0.01, "{point:?} is {error} away");
+            }
+        }
+    }
+
+    #[test]
+    fn simplify_errors() {
+        let table = Piecewise::try_from(Vec::from(SIDEARM)).unwrap();
+        assert!(matches!(
+            table.simplify_keeping(1.0, &[1, 3]),
+            Err(PiecewiseErr::IndexOutOfRange { index: 3, len: 3 })
+        ));
+        assert!(matches!(
+            table.simplify(f64::NAN),
+            Err(PiecewiseErr::InputNaN(_))
+        ));
+        assert!(matches!(
+            table.with_interp(InterpKind::LogY).unwrap().simplify(1.0),
+            Err(PiecewiseErr::UnsupportedInterp(InterpKind::LogY))
+        ));
+    }
+}
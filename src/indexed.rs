@@ -0,0 +1,177 @@
+use ordered_float::NotNan;
+use serde::{de, Deserialize, Deserializer, Serialize, Serializer};
+
+use crate::{Coord, Piecewise, PiecewiseErr};
+
+/// A table keyed by integer levels, interpolating linearly between defined levels.
+///
+/// Serializes as `[[1, 10.0], [50, 55.0]]`.
+#[derive(Debug, Clone, Hash, PartialEq, Eq)]
+pub struct IndexedPiecewise(Vec<(u32, NotNan<f64>)>);
+
+impl TryFrom<Vec<(u32, f64)>> for IndexedPiecewise {
+    type Error = PiecewiseErr;
+    fn try_from(levels: Vec<(u32, f64)>) -> Result<Self, Self::Error> {
+        let mut indexed = levels
+            .into_iter()
+            .enumerate()
+            .map(|(i, (level, y))| Ok((i, (level, NotNan::new(y)?))))
+            .collect::<Result<Vec<_>, PiecewiseErr>>()?;
+        if indexed.is_empty() {
+            return Err(PiecewiseErr::InputEmpty);
+        }
+
+        indexed.sort_by_key(|(_, level)| level.0);
+        for (sorted_index, pair) in indexed.windows(2).enumerate() {
+            let (i1, (l1, y1)) = pair[0];
+            let (i2, (l2, y2)) = pair[1];
+            if l1 == l2 && y1 != y2 {
+                return Err(PiecewiseErr::InputUndefined {
+                    x: l1 as f64,
+                    y_first: y1.into_inner(),
+                    y_second: y2.into_inner(),
+                    sorted_index,
+                    original_indices: (i1, i2),
+                });
+            }
+        }
+        let mut levels = indexed
+            .into_iter()
+            .map(|(_, level)| level)
+            .collect::<Vec<_>>();
+        //what's left are exact repeats, which carry no information
+        levels.dedup();
+
+        Ok(IndexedPiecewise(levels))
+    }
+}
+
+impl IndexedPiecewise {
+    /// The value at `level`, interpolated between the defined levels around it.
+    pub fn y_at(&self, level: u32) -> Result<f64, PiecewiseErr> {
+        let data = &self.0;
+        let index = match data.binary_search_by_key(&level, |entry| entry.0) {
+            Ok(index) => return Ok(data[index].1.into_inner()),
+            Err(index) if index == 0 || index == data.len() => {
+                return Err(PiecewiseErr::NotInDomain)
+            }
+            Err(index) => index,
+        };
+
+        let (l1, y1) = (data[index - 1].0 as f64, data[index - 1].1.into_inner());
+        let (l2, y2) = (data[index].0 as f64, data[index].1.into_inner());
+        Ok(y1 + (y2 - y1) * (level as f64 - l1) / (l2 - l1))
+    }
+
+    /// The lowest and highest defined level.
+    pub fn levels(&self) -> (u32, u32) {
+        (self.0[0].0, self.0[self.0.len() - 1].0)
+    }
+}
+
+impl From<&IndexedPiecewise> for Piecewise {
+    fn from(value: &IndexedPiecewise) -> Self {
+        let points = value
+            .0
+            .iter()
+            .map(|&(level, y)| Coord(NotNan::from(level), y))
+            .collect::<Vec<_>>();
+        Piecewise::try_from(points).expect("levels are sorted and unique")
+    }
+}
+
+//every x has to be a whole number that fits in a u32, and only linear tables
+//interpolate the same way once indexed
+impl TryFrom<&Piecewise> for IndexedPiecewise {
+    type Error = PiecewiseErr;
+    fn try_from(value: &Piecewise) -> Result<Self, Self::Error> {
+        value.check_linear()?;
+        let levels = value
+            .as_slice()
+            .iter()
+            .map(|point| {
+                let x = point.x();
+                if x.fract() != 0.0 || x < 0.0 || x > u32::MAX as f64 {
+                    return Err(PiecewiseErr::NotALevel { x });
+                }
+                Ok((x as u32, point.y()))
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+        IndexedPiecewise::try_from(levels)
+    }
+}
+
+impl Serialize for IndexedPiecewise {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_seq(&self.0)
+    }
+}
+
+impl<'de> Deserialize<'de> for IndexedPiecewise {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let levels = Vec::<(u32, f64)>::deserialize(deserializer)?;
+        IndexedPiecewise::try_from(levels).map_err(de::Error::custom)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::IndexedPiecewise;
+    use crate::tests::{table, SIDEARM};
+    use crate::{InterpKind, Piecewise, PiecewiseErr};
+
+    fn xp() -> IndexedPiecewise {
+        IndexedPiecewise::try_from(vec![(50, 55.0), (1, 10.0), (100, 80.0)]).unwrap()
+    }
+
+    #[test]
+    fn y_at() {
+        let xp = xp();
+        assert_eq!(xp.y_at(1).unwrap(), 10.0);
+        assert_eq!(xp.y_at(50).unwrap(), 55.0);
+        assert_eq!(xp.y_at(75).unwrap(), 67.5);
+        assert_eq!(xp.y_at(11).unwrap(), 10.0 + 45.0 * 10.0 / 49.0);
+        assert!(matches!(xp.y_at(0), Err(PiecewiseErr::NotInDomain)));
+        assert!(matches!(xp.y_at(101), Err(PiecewiseErr::NotInDomain)));
+        assert_eq!(xp.levels(), (1, 100));
+
+        assert!(IndexedPiecewise::try_from(vec![(1, 1.0), (1, 2.0)]).is_err());
+        assert!(IndexedPiecewise::try_from(vec![(1, f64::NAN)]).is_err());
+        assert!(IndexedPiecewise::try_from(vec![]).is_err());
+    }
+
+    #[test]
+    fn conversions() {
+        let xp = xp();
+        let exact = Piecewise::from(&xp);
+        assert_eq!(exact.y_at_x(75.0).unwrap(), 67.5);
+        assert_eq!(IndexedPiecewise::try_from(&exact).unwrap(), xp);
+
+        let sidearm = Piecewise::try_from(Vec::from(SIDEARM)).unwrap();
+        assert_eq!(
+            IndexedPiecewise::try_from(&sidearm)
+                .unwrap()
+                .y_at(95)
+                .unwrap(),
+            39.0
+        );
+
+        let fractional = table(&[(0.5, 1.0), (2.0, 1.0)]);
+        assert!(matches!(
+            IndexedPiecewise::try_from(&fractional),
+            Err(PiecewiseErr::NotALevel { x }) if x == 0.5
+        ));
+        assert!(IndexedPiecewise::try_from(&table(&[(-1.0, 1.0)])).is_err());
+        let stepped = sidearm.with_interp(InterpKind::StepPrevious).unwrap();
+        assert!(IndexedPiecewise::try_from(&stepped).is_err());
+    }
+
+    #[test]
+    fn serde() {
+        let xp = IndexedPiecewise::try_from(vec![(1, 10.0), (50, 55.0)]).unwrap();
+        let json = serde_json::to_string(&xp).unwrap();
+        assert_eq!(json, "[[1,10.0],[50,55.0]]");
+        assert_eq!(serde_json::from_str::<IndexedPiecewise>(&json).unwrap(), xp);
+        assert!(serde_json::from_str::<IndexedPiecewise>("[[1.5, 10]]").is_err());
+    }
+}
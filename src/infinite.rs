@@ -0,0 +1,57 @@
+//! Serde representation for tables that are allowed to hold infinite values.
+//!
+//! Use it with `#[serde(with = "lerp_table::infinite")]`. It reads every form the
+//! [`Piecewise`] implementation does, but lets `inf` and `-inf` through where that
+//! rejects them. NaN is still rejected.
+//!
+//! Tables are written like usual. Some formats can't hold infinite values, like JSON,
+//! where serde_json writes them as `null`, so those tables only round trip through
+//! formats that can.
+
+use serde::{Deserializer, Serialize, Serializer};
+
+use crate::{Piecewise, PiecewiseVisitor};
+
+pub fn serialize<S: Serializer>(table: &Piecewise, serializer: S) -> Result<S::Ok, S::Error> {
+    table.serialize(serializer)
+}
+
+pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Piecewise, D::Error> {
+    deserializer.deserialize_any(PiecewiseVisitor { finite: false })
+}
+
+#[cfg(test)]
+mod tests {
+    use serde::de::value::Error;
+    use serde::de::IntoDeserializer;
+    use serde::Deserialize;
+
+    use crate::Piecewise;
+
+    #[derive(Debug, Deserialize)]
+    struct Unbounded {
+        #[serde(with = "crate::infinite")]
+        curve: Piecewise,
+    }
+
+    #[test]
+    fn lets_infinity_through() {
+        let pairs = vec![
+            vec![0.0, f64::NEG_INFINITY],
+            vec![1.0, 0.0],
+            vec![f64::INFINITY, 1.0],
+        ];
+        let curve: Result<Piecewise, Error> =
+            crate::infinite::deserialize(pairs.into_deserializer());
+        let curve = curve.unwrap();
+        assert_eq!(curve.y_at_x(0.0).unwrap(), f64::NEG_INFINITY);
+        assert_eq!(curve.domain(), (0.0, f64::INFINITY));
+
+        let unbounded: Unbounded =
+            serde_json::from_str(r#"{"curve": {"-inf": 1, "0": 2}}"#).unwrap();
+        assert_eq!(unbounded.curve.domain(), (f64::NEG_INFINITY, 0.0));
+        assert!(serde_json::from_str::<Piecewise>(r#"{"-inf": 1, "0": 2}"#).is_err());
+
+        assert!(serde_json::from_str::<Unbounded>(r#"{"curve": {"NaN": 1}}"#).is_err());
+    }
+}
@@ -0,0 +1,314 @@
+use std::sync::Arc;
+
+use ordered_float::NotNan;
+use serde::{Deserialize, Serialize};
+
+use crate::{lerp, Coord, Piecewise, PiecewiseErr};
+
+/// How a segment of a table interpolates between its two knots.
+///
+/// Exact geometry between two tables, such as areas, distances and pointwise extremes,
+/// is only computed for linear tables and errors with
+/// [`PiecewiseErr::UnsupportedInterp`] for the other kinds.
+#[derive(
+    Debug, Clone, Copy, Default, Hash, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize,
+)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+pub enum InterpKind {
+    #[default]
+    Linear,
+    /// A straight line through `ln y`, i.e. geometric interpolation. Every y must be positive.
+    LogY,
+    /// A straight line through `ln y` against `ln x`. Every x and y must be positive.
+    LogLog,
+    /// Holds the left knot's y up to, but not including, the right knot.
+    StepPrevious,
+    /// Holds the right knot's y from just after the left knot, the mirror image of
+    /// [`InterpKind::StepPrevious`].
+    StepNext,
+}
+
+impl InterpKind {
+    /// Errors if `point` can't be used with this kind.
+    pub(crate) fn check(self, point: Coord) -> Result<(), PiecewiseErr> {
+        let (x, y) = (point.x(), point.y());
+        let valid = match self {
+            InterpKind::Linear | InterpKind::StepPrevious | InterpKind::StepNext => true,
+            InterpKind::LogY => y > 0.0,
+            InterpKind::LogLog => x > 0.0 && y > 0.0,
+        };
+        if !valid {
+            return Err(PiecewiseErr::NotPositive { x, y });
+        }
+        Ok(())
+    }
+
+    /// Maps a point into the space where this kind is a straight line.
+    fn forward(self, x: f64, y: f64) -> (f64, f64) {
+        match self {
+            InterpKind::Linear | InterpKind::StepPrevious | InterpKind::StepNext => (x, y),
+            InterpKind::LogY => (x, y.ln()),
+            InterpKind::LogLog => (x.ln(), y.ln()),
+        }
+    }
+
+    /// The value at `x` of the segment through `left` and `right`, also used past its ends.
+    pub(crate) fn interpolate(self, left: Coord, right: Coord, x: NotNan<f64>) -> f64 {
+        match self {
+            InterpKind::Linear => return lerp(left, right, x),
+            InterpKind::StepPrevious => return left.y(),
+            InterpKind::StepNext => return right.y(),
+            _ => (),
+        }
+        let (x1, y1) = self.forward(left.x(), left.y());
+        let (x2, y2) = self.forward(right.x(), right.y());
+        let (x, _) = self.forward(x.into_inner(), 1.0);
+        (y1 + (x - x1) * (y2 - y1) / (x2 - x1)).exp()
+    }
+
+    /// [`InterpKind::interpolate`], erroring with [`PiecewiseErr::NonFiniteResult`] when
+    /// knots with finite y overflow along the way, or when infinite ones meet and give
+    /// NaN. Infinite knots otherwise spread on purpose.
+    pub(crate) fn interpolate_finite(
+        self,
+        left: Coord,
+        right: Coord,
+        x: NotNan<f64>,
+    ) -> Result<f64, PiecewiseErr> {
+        let y = self.interpolate(left, right, x);
+        let overflowed = !y.is_finite() && left.y().is_finite() && right.y().is_finite();
+        if overflowed || y.is_nan() {
+            return Err(PiecewiseErr::NonFiniteResult { x: x.into_inner() });
+        }
+        Ok(y)
+    }
+
+    /// The exact integral from `lo` to `hi` of the segment through `left` and `right`,
+    /// where `left.x() <= lo <= hi <= right.x()`.
+    pub(crate) fn integral(
+        self,
+        left: Coord,
+        right: Coord,
+        lo: NotNan<f64>,
+        hi: NotNan<f64>,
+    ) -> f64 {
+        let width = (hi - lo).into_inner();
+        let (y_lo, y_hi) = (
+            self.interpolate(left, right, lo),
+            self.interpolate(left, right, hi),
+        );
+        match self {
+            InterpKind::Linear => width * (y_lo + y_hi) / 2.0,
+            InterpKind::StepPrevious => width * left.y(),
+            InterpKind::StepNext => width * right.y(),
+            _ if y_lo == y_hi => width * y_lo,
+            //the width times the logarithmic mean of the ends
+            InterpKind::LogY => width * (y_hi - y_lo) / (y_hi.ln() - y_lo.ln()),
+            //y = c * x^k integrates to x * y / (k + 1), or to c * ln x for k = -1
+            InterpKind::LogLog => {
+                let k = (right.y() / left.y()).ln() / (right.x() / left.x()).ln();
+                let (lo, hi) = (lo.into_inner(), hi.into_inner());
+                if k == -1.0 {
+                    lo * y_lo * (hi / lo).ln()
+                } else {
+                    (hi * y_hi - lo * y_lo) / (k + 1.0)
+                }
+            }
+        }
+    }
+
+    /// The x where the segment from `(x1, y1)` to `(x2, y2)` reaches `target`.
+    ///
+    /// Landing exactly on an endpoint returns that endpoint so knots aren't blurred by rounding.
+    /// A step jumps past the target at the knot it jumps at: the right one for
+    /// [`InterpKind::StepPrevious`] and the left one for [`InterpKind::StepNext`].
+    pub(crate) fn crossing(self, x1: f64, y1: f64, x2: f64, y2: f64, target: f64) -> f64 {
+        if y1 == target || self == InterpKind::StepNext {
+            return x1;
+        } else if y2 == target || self == InterpKind::StepPrevious {
+            return x2;
+        }
+        let (fx1, fy1) = self.forward(x1, y1);
+        let (fx2, fy2) = self.forward(x2, y2);
+        let (_, target) = self.forward(1.0, target);
+        let x = fx1 + (fx2 - fx1) * (target - fy1) / (fy2 - fy1);
+        match self {
+            InterpKind::LogLog => x.exp(),
+            _ => x,
+        }
+    }
+}
+
+impl Piecewise {
+    /// Switches every segment of the table to interpolating with `kind`.
+    ///
+    /// The log kinds error with [`PiecewiseErr::NotPositive`] on the first knot they
+    /// can't take the log of.
+    pub fn with_interp(mut self, kind: InterpKind) -> Result<Piecewise, PiecewiseErr> {
+        for &point in self.as_slice() {
+            kind.check(point)?;
+        }
+        Arc::make_mut(&mut self.1).fill(kind);
+        Ok(self)
+    }
+
+    /// Switches the segment between knots `segment_index` and `segment_index + 1`
+    /// to interpolating with `kind`. Segments default to [`InterpKind::Linear`].
+    pub fn set_segment_kind(
+        &mut self,
+        segment_index: usize,
+        kind: InterpKind,
+    ) -> Result<(), PiecewiseErr> {
+        let len = self.1.len();
+        if segment_index >= len {
+            return Err(PiecewiseErr::IndexOutOfRange {
+                index: segment_index,
+                len,
+            });
+        }
+        kind.check(self.0[segment_index])?;
+        kind.check(self.0[segment_index + 1])?;
+        Arc::make_mut(&mut self.1)[segment_index] = kind;
+        Ok(())
+    }
+
+    /// Errors with [`PiecewiseErr::UnsupportedInterp`] on the first segment that isn't
+    /// linear, for operations whose results are only exact for linear tables.
+    pub(crate) fn check_linear(&self) -> Result<(), PiecewiseErr> {
+        match self.1.iter().find(|&&kind| kind != InterpKind::Linear) {
+            Some(&kind) => Err(PiecewiseErr::UnsupportedInterp(kind)),
+            None => Ok(()),
+        }
+    }
+
+    /// The interpolation kind of every segment, in order.
+    pub fn segment_kinds(&self) -> &[InterpKind] {
+        &self.1
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::InterpKind;
+    use crate::tests::table;
+    use crate::{Piecewise, PiecewiseErr};
+
+    #[test]
+    fn log_y_midpoint() {
+        let table = table(&[(0.0, 1.0), (2.0, 100.0)])
+            .with_interp(InterpKind::LogY)
+            .unwrap();
+        assert!((table.y_at_x(1.0).unwrap() - 10.0).abs() < 1e-12);
+        assert_eq!(table.y_at_x(2.0).unwrap(), 100.0);
+        assert!(table.y_at_x(3.0).is_err());
+        assert!(table.y_at_x(f64::NAN).is_err());
+    }
+
+    #[test]
+    fn log_log_power_law() {
+        //y = x² is a straight line in log-log space
+        let table = table(&[(1.0, 1.0), (10.0, 100.0)])
+            .with_interp(InterpKind::LogLog)
+            .unwrap();
+        assert!((table.y_at_x(5.0).unwrap() - 25.0).abs() < 1e-12);
+        let crossing = table.first_x_where(49.0, crate::ScanDirection::FromLeft);
+        assert!((crossing.unwrap().unwrap() - 7.0).abs() < 1e-12);
+    }
+
+    #[test]
+    fn serde_keeps_kind() {
+        let table = table(&[(1.0, 1.0), (10.0, 100.0)])
+            .with_interp(InterpKind::LogLog)
+            .unwrap();
+        let json = serde_json::to_string(&table).unwrap();
+        assert_eq!(
+            json,
+            r#"{"points":[[1.0,1.0],[10.0,100.0]],"interp":"LogLog"}"#
+        );
+        assert_eq!(serde_json::from_str::<Piecewise>(&json).unwrap(), table);
+
+        let points_only: Piecewise =
+            serde_json::from_str(r#"{"points": [[0, 1], [1, 1]]}"#).unwrap();
+        assert_eq!(points_only.segment_kinds(), [InterpKind::Linear]);
+        assert!(
+            serde_json::from_str::<Piecewise>(r#"{"points": [[0, 0]], "interp": "LogY"}"#).is_err()
+        );
+        assert!(serde_json::from_str::<Piecewise>(r#"{"interp": "LogY"}"#).is_err());
+        assert!(serde_json::from_str::<Piecewise>(r#"{"points": [[0, 1]], "0": 1}"#).is_err());
+    }
+
+    #[test]
+    fn log_rejects_non_positive() {
+        let zero_y = table(&[(0.0, 0.0), (1.0, 1.0)]);
+        assert!(matches!(
+            zero_y.clone().with_interp(InterpKind::LogY),
+            Err(PiecewiseErr::NotPositive { x, y }) if x == 0.0 && y == 0.0
+        ));
+
+        let zero_x = table(&[(0.0, 1.0), (1.0, 1.0)]);
+        assert!(zero_x.clone().with_interp(InterpKind::LogY).is_ok());
+        assert!(zero_x.with_interp(InterpKind::LogLog).is_err());
+    }
+
+    fn stepped() -> Piecewise {
+        let mut table = table(&[(0.0, 0.0), (10.0, 10.0), (20.0, 30.0), (30.0, 40.0)]);
+        table.set_segment_kind(1, InterpKind::StepPrevious).unwrap();
+        table
+    }
+
+    #[test]
+    fn per_segment_kinds() {
+        let table = stepped();
+        assert_eq!(
+            table.segment_kinds(),
+            [
+                InterpKind::Linear,
+                InterpKind::StepPrevious,
+                InterpKind::Linear
+            ]
+        );
+        assert_eq!(table.y_at_x(5.0).unwrap(), 5.0);
+        assert_eq!(table.y_at_x(10.0).unwrap(), 10.0);
+        assert_eq!(table.y_at_x(15.0).unwrap(), 10.0);
+        assert_eq!(table.y_at_x(19.999).unwrap(), 10.0);
+        assert_eq!(table.y_at_x(20.0).unwrap(), 30.0);
+        assert_eq!(table.y_at_x(20.001).unwrap(), 30.001);
+
+        let mut table = table;
+        assert!(matches!(
+            table.set_segment_kind(3, InterpKind::Linear),
+            Err(PiecewiseErr::IndexOutOfRange { index: 3, len: 3 })
+        ));
+        assert!(table.set_segment_kind(0, InterpKind::LogY).is_err());
+        assert!(table.set_segment_kind(2, InterpKind::LogY).is_ok());
+
+        table.set_segment_kind(1, InterpKind::StepNext).unwrap();
+        assert_eq!(table.y_at_x(10.0).unwrap(), 10.0);
+        assert_eq!(table.y_at_x(10.001).unwrap(), 30.0);
+        assert_eq!(table.y_at_x(20.0).unwrap(), 30.0);
+        assert_eq!(table.support(20.0).unwrap(), [(10.0, 30.0)]);
+    }
+
+    #[test]
+    fn per_segment_serde() {
+        let table = stepped();
+        let json = serde_json::to_string(&table).unwrap();
+        assert_eq!(
+            json,
+            r#"{"points":[[0.0,0.0],[10.0,10.0],[20.0,30.0],[30.0,40.0]],"segments":["Linear","StepPrevious","Linear"]}"#
+        );
+        assert_eq!(serde_json::from_str::<Piecewise>(&json).unwrap(), table);
+
+        let short = r#"{"points":[[0,0],[10,10],[20,30]],"segments":["Linear"]}"#;
+        assert!(serde_json::from_str::<Piecewise>(short).is_err());
+        let both = r#"{"points":[[0,1],[10,10]],"segments":["LogY"],"interp":"LogY"}"#;
+        assert!(serde_json::from_str::<Piecewise>(both).is_err());
+
+        //all linear tables keep the plain array form
+        let plain = crate::tests::table(&[(0.0, 0.0), (1.0, 1.0)]);
+        assert_eq!(
+            serde_json::to_string(&plain).unwrap(),
+            "[[0.0,0.0],[1.0,1.0]]"
+        );
+    }
+}
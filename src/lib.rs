@@ -1,29 +1,170 @@
 use ordered_float::{FloatIsNan, NotNan};
-use schemars::JsonSchema;
-use serde::{Deserialize, Serialize};
+use serde::de::{self, DeserializeSeed, MapAccess, SeqAccess, Visitor};
+use serde::ser::SerializeStruct;
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use std::fmt;
+use std::str::FromStr;
+use std::sync::Arc;
 use thiserror::Error;
 
-#[derive(
-    Debug, Clone, Hash, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize, JsonSchema,
-)]
-#[serde(try_from = "Vec<Coord>", into = "Vec<(NotNan<f64>, NotNan<f64>)>")]
-pub struct Piecewise(Vec<Coord>);
+mod analysis;
+mod authored;
+mod baked;
+mod band;
+mod batch;
+mod compact;
+pub mod delta_format;
+mod edit;
+mod family;
+#[cfg(feature = "ffi")]
+pub mod ffi;
+mod fit;
+mod indexed;
+pub mod infinite;
+mod interp;
+mod ops;
+mod plot;
+pub mod presorted;
+#[cfg(feature = "python")]
+pub mod python;
+mod recorder;
+mod rounded;
+#[cfg(feature = "schemars")]
+mod schema;
+mod set;
+#[cfg(feature = "proptest")]
+pub mod strategy;
+mod tagged;
+#[cfg(feature = "wasm")]
+pub mod wasm;
+mod wrapping;
+
+pub use analysis::{PiecewiseReport, ScanDirection};
+pub use authored::PiecewiseAuthored;
+pub use baked::{BakedPiecewise, CrossingPick};
+pub use band::PiecewiseBand;
+pub use compact::TableParseError;
+pub use edit::GuardKind;
+pub use family::{ParameterPolicy, PiecewiseFamily};
+pub use fit::Monotonicity;
+pub use indexed::IndexedPiecewise;
+pub use interp::InterpKind;
+pub use recorder::Recorder;
+pub use rounded::RoundedPiecewise;
+pub use set::PiecewiseSet;
+pub use tagged::PiecewiseTagged;
+pub use wrapping::WrappingPiecewise;
+
+/// A table of knots, interpolated segment by segment.
+///
+/// The knots and kinds are shared behind [`Arc`]s, so cloning is cheap and the
+/// methods that change a table copy them first if another clone still holds them.
+#[derive(Debug, Clone, Hash, PartialEq, Eq, PartialOrd, Ord)]
+pub struct Piecewise(Arc<Vec<Coord>>, Arc<Vec<InterpKind>>);
 
 #[derive(Error, Debug)]
 pub enum PiecewiseErr {
     #[error("The provided segment is empty")]
     InputEmpty,
-    #[error("The function is undefined")]
-    InputUndefined,
+    /// Two knots share an x but not a y. `sorted_index` is the position of the first
+    /// of them once sorted and `original_indices` their positions in the input.
+    #[error(
+        "The function is undefined at x = {x}, which is given both y = {y_first} and \
+         y = {y_second} (input indices {} and {}, sorted index {sorted_index})",
+        original_indices.0,
+        original_indices.1
+    )]
+    InputUndefined {
+        x: f64,
+        y_first: f64,
+        y_second: f64,
+        sorted_index: usize,
+        original_indices: (usize, usize),
+    },
     #[error("The value is not in the domain")]
     NotInDomain,
+    #[error("The knot at index {index} is not greater than the one before it")]
+    KnotsNotIncreasing { index: usize },
+    #[error("There are not enough samples to determine the knots between {left} and {right}")]
+    Underdetermined { left: f64, right: f64 },
+    #[error("Log interpolation needs positive values but found a knot at ({x}, {y})")]
+    NotPositive { x: f64, y: f64 },
+    #[error("Index {index} is out of range for length {len}")]
+    IndexOutOfRange { index: usize, len: usize },
+    #[error("{x} is not a valid level")]
+    NotALevel { x: f64 },
+    #[error("{0:?} interpolation is not supported here")]
+    UnsupportedInterp(InterpKind),
+    #[error("A period of {0} is not positive and finite")]
+    InvalidPeriod(f64),
+    #[error("A grid spacing of {0} is not finite and non-negative")]
+    InvalidGrid(f64),
+    #[error("A window of {0} is not odd")]
+    InvalidWindow(usize),
+    #[error("A resolution of {0} is out of range")]
+    InvalidResolution(usize),
+    #[error("The domains of the tables do not overlap")]
+    DisjointDomains,
+    #[error("Expected {expected} values but found {found}")]
+    LengthMismatch { expected: usize, found: usize },
     #[error(transparent)]
     InputNaN(#[from] FloatIsNan),
+    #[error("The value at index {index} is NaN")]
+    InputNaNAt { index: usize },
+    /// Interpolating between knots with finite y overflowed, usually because the
+    /// segment is very steep.
+    #[error("Evaluating at x = {x} does not give a finite value")]
+    NonFiniteResult { x: f64 },
+    #[error("The lower bound {lower} is above the upper bound {upper} at x = {x}")]
+    BandCrossed { x: f64, lower: f64, upper: f64 },
+    #[error("The parameter at index {index} is not finite")]
+    NonFiniteParameter { index: usize },
+    #[error("[{min}, {max}] is not an interval with its min below its max")]
+    InvalidInterval { min: f64, max: f64 },
+    #[error("The domain is the single point x = {x}")]
+    DegenerateDomain { x: f64 },
+    #[error("The knot at index {index} is not finite")]
+    NonFiniteKnot { index: usize },
+    #[error("A guard at x = {x} is not outside the domain [{min}, {max}]")]
+    GuardInsideDomain { x: f64, min: f64, max: f64 },
+}
+
+/// Why [`Piecewise::from_sorted_iter`] failed, with the index of the element at fault.
+#[derive(Error, Debug)]
+pub enum FromIterError<E> {
+    #[error("The iterator was empty")]
+    InputEmpty,
+    #[error("Element {index} could not be read: {error}")]
+    Source { index: usize, error: E },
+    #[error("Element {index} is invalid: {error}")]
+    Invalid { index: usize, error: PiecewiseErr },
+}
+
+/// Why a string couldn't be parsed as a [`Coord`].
+#[derive(Error, Debug, Clone, PartialEq, Eq)]
+pub enum CoordParseError {
+    #[error("Expected 2 comma separated fields but found {0}")]
+    WrongFieldCount(usize),
+    #[error("{0:?} is not a number")]
+    NotANumber(String),
+    #[error("A coordinate can't be NaN")]
+    NaN,
+}
+
+/// How x values outside a table's domain are evaluated.
+#[derive(Debug, Clone, Copy, Default, Hash, PartialEq, Eq)]
+pub enum Extrapolate {
+    /// Hold the y value of the nearest endpoint.
+    #[default]
+    Clamp,
+    /// Continue the slope of the segment at that end of the table.
+    Linear,
 }
 
-#[derive(
-    Debug, Clone, Copy, Hash, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize, JsonSchema,
-)]
+/// A knot of a table. Ordered by x, then y.
+///
+/// Deserializes from an `[x, y]` pair, rejecting infinite values as well as NaN.
+#[derive(Debug, Clone, Copy, Hash, PartialEq, Eq, PartialOrd, Ord, Serialize)]
 pub struct Coord(NotNan<f64>, NotNan<f64>);
 
 impl From<Coord> for (NotNan<f64>, NotNan<f64>) {
@@ -43,38 +184,171 @@ impl<X: Into<f64>, Y: Into<f64>> TryFrom<(X, Y)> for Coord {
 }
 
 impl Coord {
+    /// # Safety
+    /// Neither component of `value` may be NaN.
     pub const unsafe fn new_unchecked(value: (f64, f64)) -> Self {
         Self(
             NotNan::new_unchecked(value.0),
             NotNan::new_unchecked(value.1),
         )
     }
+    pub fn x(&self) -> f64 {
+        self.0.into_inner()
+    }
+    pub fn y(&self) -> f64 {
+        self.1.into_inner()
+    }
+    /// Orders by x alone, the order knots have in a table.
+    pub fn cmp_by_x(a: &Coord, b: &Coord) -> std::cmp::Ordering {
+        a.0.cmp(&b.0)
+    }
     pub const fn zero() -> Self {
         unsafe { Self(NotNan::new_unchecked(0.0), NotNan::new_unchecked(0.0)) }
     }
 }
+//accepts `x,y` with optional whitespace around the fields and optional parentheses
+//around the whole, like `(12.5, 40)`
+impl FromStr for Coord {
+    type Err = CoordParseError;
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let s = s.trim();
+        let s = s
+            .strip_prefix('(')
+            .and_then(|inner| inner.strip_suffix(')'))
+            .unwrap_or(s);
+        let fields = s.split(',').map(str::trim).collect::<Vec<_>>();
+        let [x, y] = fields[..] else {
+            return Err(CoordParseError::WrongFieldCount(fields.len()));
+        };
+        let parse = |field: &str| {
+            f64::from_str(field).map_err(|_| CoordParseError::NotANumber(field.to_owned()))
+        };
+        Coord::try_from((parse(x)?, parse(y)?)).map_err(|_| CoordParseError::NaN)
+    }
+}
+
+impl<'de> Deserialize<'de> for Coord {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        checked_pair(<(f64, f64)>::deserialize(deserializer)?, true).map_err(de::Error::custom)
+    }
+}
+
+/// Checks an `(x, y)` pair read by serde, describing the first bad value if there is
+/// one. Infinite values are only let through when `finite` is false.
+pub(crate) fn checked_pair((x, y): (f64, f64), finite: bool) -> Result<Coord, String> {
+    for (axis, value) in [("x", x), ("y", y)] {
+        if value.is_nan() {
+            return Err(format!("the {axis} value is NaN"));
+        }
+        if finite && value.is_infinite() {
+            return Err(format!("the {axis} value {value} is not finite"));
+        }
+    }
+    Ok(unsafe { Coord::new_unchecked((x, y)) })
+}
+
+/// Writes the canonical `x,y` form, which parses back to the same coordinate.
+impl fmt::Display for Coord {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{},{}", self.0, self.1)
+    }
+}
+
 //takes a bit to load, but verification is verification
 impl TryFrom<Vec<Coord>> for Piecewise {
     type Error = PiecewiseErr;
-    fn try_from(mut points: Vec<Coord>) -> Result<Self, Self::Error> {
+    fn try_from(points: Vec<Coord>) -> Result<Self, Self::Error> {
         match points.len() {
             0 => return Err(PiecewiseErr::InputEmpty),
-            1 => return Ok(Piecewise(points.into())),
+            1 => return Ok(Piecewise::from_parts(points, Vec::new())),
             _ => (),
         }
 
-        points.sort_by(|a, b| a.0.cmp(&b.0));
+        //the original indices ride along through the sort so errors can point at the input
+        let mut indexed = points.into_iter().enumerate().collect::<Vec<_>>();
+        indexed.sort_by_key(|(_, point)| point.0);
 
-        for point_pair in points.windows(2) {
-            let Coord(x1, y1) = point_pair[0];
-            let Coord(x2, y2) = point_pair[1];
+        for (sorted_index, pair) in indexed.windows(2).enumerate() {
+            let (i1, Coord(x1, y1)) = pair[0];
+            let (i2, Coord(x2, y2)) = pair[1];
 
             if x2 == x1 && y2 != y1 {
-                return Err(PiecewiseErr::InputUndefined);
+                return Err(PiecewiseErr::InputUndefined {
+                    x: x1.into_inner(),
+                    y_first: y1.into_inner(),
+                    y_second: y2.into_inner(),
+                    sorted_index,
+                    original_indices: (i1, i2),
+                });
             }
         }
 
-        Ok(Piecewise(points.into()))
+        let points = indexed
+            .into_iter()
+            .map(|(_, point)| point)
+            .collect::<Vec<_>>();
+        let kinds = vec![InterpKind::Linear; points.len() - 1];
+        Ok(Piecewise::from_parts(points, kinds))
+    }
+}
+
+impl Piecewise {
+    /// Builds a table from `(x, y)` pairs that are already in ascending x, checking
+    /// each as it arrives instead of collecting and sorting them.
+    ///
+    /// The first error from the iterator itself is passed through as
+    /// [`FromIterError::Source`]. Repeating an x is allowed as long as the y repeats too,
+    /// like [`Piecewise::try_from`].
+    pub fn from_sorted_iter<E, I>(iter: I) -> Result<Piecewise, FromIterError<E>>
+    where
+        I: IntoIterator<Item = Result<(f64, f64), E>>,
+    {
+        let iter = iter.into_iter();
+        let mut points: Vec<Coord> = Vec::with_capacity(iter.size_hint().0);
+        for (index, item) in iter.enumerate() {
+            let invalid = |error| FromIterError::Invalid { index, error };
+            let pair = item.map_err(|error| FromIterError::Source { index, error })?;
+            let point = Coord::try_from(pair).map_err(|err| invalid(err.into()))?;
+            if let Some(last) = points.last() {
+                if point.0 < last.0 {
+                    return Err(invalid(PiecewiseErr::KnotsNotIncreasing { index }));
+                }
+                if point.0 == last.0 && point.1 != last.1 {
+                    return Err(invalid(PiecewiseErr::InputUndefined {
+                        x: last.x(),
+                        y_first: last.y(),
+                        y_second: point.y(),
+                        sorted_index: index - 1,
+                        original_indices: (index - 1, index),
+                    }));
+                }
+            }
+            points.push(point);
+        }
+        if points.is_empty() {
+            return Err(FromIterError::InputEmpty);
+        }
+
+        let kinds = vec![InterpKind::Linear; points.len() - 1];
+        Ok(Piecewise::from_parts(points, kinds))
+    }
+
+    /// `y = x` over `[min_x, max_x]`, which has to have `min_x < max_x` and errors with
+    /// [`PiecewiseErr::InvalidInterval`] otherwise.
+    pub fn identity(min_x: f64, max_x: f64) -> Result<Piecewise, PiecewiseErr> {
+        let (min_x, max_x) = (NotNan::new(min_x)?, NotNan::new(max_x)?);
+        if min_x >= max_x {
+            return Err(PiecewiseErr::InvalidInterval {
+                min: min_x.into_inner(),
+                max: max_x.into_inner(),
+            });
+        }
+        Piecewise::try_from(vec![Coord(min_x, min_x), Coord(max_x, max_x)])
+    }
+
+    /// The straight line through `a` and `b`, in either order.
+    pub fn line(a: (f64, f64), b: (f64, f64)) -> Result<Piecewise, PiecewiseErr> {
+        Piecewise::try_from(vec![Coord::try_from(a)?, Coord::try_from(b)?])
     }
 }
 
@@ -88,43 +362,450 @@ impl From<Piecewise> for Vec<(NotNan<f64>, NotNan<f64>)> {
     }
 }
 
+//linear tables are written as a plain array of [x, y] pairs, tables using one other
+//kind as `{"points": [[x, y], ...], "interp": "LogY"}` and tables mixing kinds as
+//`{"points": [[x, y], ...], "segments": ["Linear", "StepPrevious", ...]}`
+impl Serialize for Piecewise {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let kinds = self.segment_kinds();
+        let uniform = kinds.iter().all(|&kind| kind == kinds[0]);
+        if kinds.is_empty() || (uniform && kinds[0] == InterpKind::Linear) {
+            return serializer.collect_seq(self.as_slice());
+        }
+        let mut state = serializer.serialize_struct("Piecewise", 2)?;
+        state.serialize_field("points", self.as_slice())?;
+        if uniform {
+            state.serialize_field("interp", &kinds[0])?;
+        } else {
+            state.serialize_field("segments", kinds)?;
+        }
+        state.end()
+    }
+}
+
+//accepts the array form `[[0, 18], [90, 36]]`, the map form `{"0": 18, "90": 36}`
+//or the extended forms `{"points": [[0, 18], [90, 36]], "interp": "LogY"}` and
+//`{"points": [[0, 18], [90, 36]], "segments": ["LogY"]}`, where the segments are
+//in the order of the sorted points
+impl<'de> Deserialize<'de> for Piecewise {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        deserializer.deserialize_any(PiecewiseVisitor { finite: true })
+    }
+}
+
+/// Reads every form [`Piecewise`] deserializes from, letting infinite values through
+/// unless `finite` is set.
+pub(crate) struct PiecewiseVisitor {
+    pub(crate) finite: bool,
+}
+
+impl<'de> Visitor<'de> for PiecewiseVisitor {
+    type Value = Piecewise;
+
+    fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        formatter.write_str("a sequence of [x, y] pairs, a map of x to y or a map of fields")
+    }
+
+    fn visit_seq<A: SeqAccess<'de>>(self, seq: A) -> Result<Self::Value, A::Error> {
+        let points = Knots {
+            finite: self.finite,
+        }
+        .visit_seq(seq)?;
+        Piecewise::try_from(points).map_err(de::Error::custom)
+    }
+
+    fn visit_map<A: MapAccess<'de>>(self, mut map: A) -> Result<Self::Value, A::Error> {
+        let first = map.next_key::<MapKey>()?;
+        if let Some(MapKey::X(x)) = first {
+            let mut points = Vec::with_capacity(map.size_hint().unwrap_or(0) + 1);
+            let mut next = Some((x, map.next_value::<f64>()?));
+            while let Some((x, y)) = next {
+                let point = checked_pair((x, y), self.finite).map_err(|err| {
+                    de::Error::custom(format!("the entry for x = {x} is invalid: {err}"))
+                })?;
+                points.push(point);
+                next = match map.next_key::<MapKey>()? {
+                    Some(MapKey::X(x)) => Some((x, map.next_value()?)),
+                    Some(_) => return Err(de::Error::custom("x keys can't be mixed with fields")),
+                    None => None,
+                };
+            }
+            return Piecewise::try_from(points).map_err(de::Error::custom);
+        }
+
+        let (mut points, mut interp, mut segments) = (None, None, None);
+        let mut key = first;
+        while let Some(field) = key {
+            match field {
+                MapKey::Points => {
+                    points = Some(map.next_value_seed(Knots {
+                        finite: self.finite,
+                    })?)
+                }
+                MapKey::Interp => interp = Some(map.next_value::<InterpKind>()?),
+                MapKey::Segments => segments = Some(map.next_value::<Vec<InterpKind>>()?),
+                MapKey::X(_) => return Err(de::Error::custom("x keys can't be mixed with fields")),
+            }
+            key = map.next_key()?;
+        }
+        let points = points.ok_or_else(|| de::Error::missing_field("points"))?;
+        let mut table = Piecewise::try_from(points).map_err(de::Error::custom)?;
+        match (interp, segments) {
+            (Some(_), Some(_)) => {
+                return Err(de::Error::custom(
+                    "only one of interp and segments can be given",
+                ))
+            }
+            (Some(kind), None) => table = table.with_interp(kind).map_err(de::Error::custom)?,
+            (None, Some(kinds)) => {
+                if kinds.len() != table.1.len() {
+                    return Err(de::Error::invalid_length(
+                        kinds.len(),
+                        &"one kind per segment",
+                    ));
+                }
+                for (index, kind) in kinds.into_iter().enumerate() {
+                    table
+                        .set_segment_kind(index, kind)
+                        .map_err(de::Error::custom)?;
+                }
+            }
+            (None, None) => (),
+        }
+        Ok(table)
+    }
+}
+
+/// A sequence of `[x, y]` pairs, with errors giving the index of the bad pair.
+/// Infinite values are only let through when `finite` is false.
+pub(crate) struct Knots {
+    pub(crate) finite: bool,
+}
+
+impl<'de> DeserializeSeed<'de> for Knots {
+    type Value = Vec<Coord>;
+
+    fn deserialize<D: Deserializer<'de>>(self, deserializer: D) -> Result<Self::Value, D::Error> {
+        deserializer.deserialize_seq(self)
+    }
+}
+
+impl<'de> Visitor<'de> for Knots {
+    type Value = Vec<Coord>;
+
+    fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        formatter.write_str("a sequence of [x, y] pairs")
+    }
+
+    fn visit_seq<A: SeqAccess<'de>>(self, mut seq: A) -> Result<Self::Value, A::Error> {
+        let mut points = Vec::with_capacity(seq.size_hint().unwrap_or(0));
+        while let Some(pair) = seq.next_element::<(f64, f64)>()? {
+            let point = checked_pair(pair, self.finite).map_err(|err| {
+                de::Error::custom(format!(
+                    "the knot at index {} is invalid: {err}",
+                    points.len()
+                ))
+            })?;
+            points.push(point);
+        }
+        Ok(points)
+    }
+}
+
+/// A key of the map forms: either an x value, given as a number or as a string
+/// holding one, or a field of the extended form.
+enum MapKey {
+    X(f64),
+    Points,
+    Interp,
+    Segments,
+}
+
+impl<'de> Deserialize<'de> for MapKey {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        deserializer.deserialize_any(MapKeyVisitor)
+    }
+}
+
+struct MapKeyVisitor;
+
+impl<'de> Visitor<'de> for MapKeyVisitor {
+    type Value = MapKey;
+
+    fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        formatter.write_str("a number, a string containing a number or a field name")
+    }
+
+    fn visit_str<E: de::Error>(self, v: &str) -> Result<Self::Value, E> {
+        match v {
+            "points" => Ok(MapKey::Points),
+            "interp" => Ok(MapKey::Interp),
+            "segments" => Ok(MapKey::Segments),
+            _ => f64::from_str(v)
+                .map(MapKey::X)
+                .map_err(|_| E::custom(format!("the key {v:?} is not a number"))),
+        }
+    }
+
+    fn visit_f64<E: de::Error>(self, v: f64) -> Result<Self::Value, E> {
+        Ok(MapKey::X(v))
+    }
+
+    fn visit_i64<E: de::Error>(self, v: i64) -> Result<Self::Value, E> {
+        Ok(MapKey::X(v as f64))
+    }
+
+    fn visit_u64<E: de::Error>(self, v: u64) -> Result<Self::Value, E> {
+        Ok(MapKey::X(v as f64))
+    }
+}
+
 impl Piecewise {
+    /// Wraps already validated knots and kinds.
+    pub(crate) fn from_parts(points: Vec<Coord>, kinds: Vec<InterpKind>) -> Piecewise {
+        Piecewise(Arc::new(points), Arc::new(kinds))
+    }
     fn as_slice(&self) -> &[Coord] {
         self.0.as_slice()
     }
+    /// The knots, sorted by x.
+    pub fn points(&self) -> &[Coord] {
+        self.as_slice()
+    }
+    /// The smallest and largest x of the table.
+    pub fn domain(&self) -> (f64, f64) {
+        let data = self.as_slice();
+        (data[0].0.into_inner(), data[data.len() - 1].0.into_inner())
+    }
+    /// Where a knot at `x` would go to keep the knots sorted: the index of the first
+    /// knot whose x isn't less than it. NaN goes first.
+    pub fn insertion_index(&self, x: f64) -> usize {
+        self.as_slice().partition_point(|point| point.x() < x)
+    }
+    /// The index of the last knot whose x is at most `x`, or `None` below the first
+    /// knot.
+    ///
+    /// A value exactly on a knot gives that knot, or the last of its repeats. Values
+    /// past the last knot give the last knot. This is the same search
+    /// [`Piecewise::y_at_x`] uses, so a value between two knots finds the left knot of
+    /// the segment it's evaluated on.
+    pub fn knot_index_at_or_before(&self, x: f64) -> Result<Option<usize>, PiecewiseErr> {
+        let value = NotNan::new(x)?;
+        let data = self.as_slice();
+        let last = data.len() - 1;
+        if value < data[0].0 {
+            return Ok(None);
+        } else if value > data[last].0 {
+            return Ok(Some(last));
+        }
+        Ok(Some(match locate(data, value)? {
+            Position::Knot(i) => i + data[i + 1..].iter().take_while(|p| p.0 == value).count(),
+            Position::Between(i) => i - 1,
+        }))
+    }
+    /// The index of the first knot whose x is at least `x`, or `None` above the last
+    /// knot.
+    ///
+    /// A value exactly on a knot gives that knot, or the first of its repeats. Values
+    /// before the first knot give the first knot. A value between two knots finds the
+    /// right knot of the segment [`Piecewise::y_at_x`] evaluates it on.
+    pub fn knot_index_at_or_after(&self, x: f64) -> Result<Option<usize>, PiecewiseErr> {
+        let value = NotNan::new(x)?;
+        let data = self.as_slice();
+        if value < data[0].0 {
+            return Ok(Some(0));
+        } else if value > data[data.len() - 1].0 {
+            return Ok(None);
+        }
+        Ok(Some(match locate(data, value)? {
+            Position::Knot(i) => i - data[..i].iter().rev().take_while(|p| p.0 == value).count(),
+            Position::Between(i) => i,
+        }))
+    }
+    /// The smallest and largest y of the table.
+    ///
+    /// Every kind of segment is monotonic between its knots, so this bounds every
+    /// in domain value.
+    pub fn range(&self) -> (f64, f64) {
+        let ys = self.as_slice().iter().map(|point| point.y());
+        ys.fold((f64::INFINITY, f64::NEG_INFINITY), |(min, max), y| {
+            (min.min(y), max.max(y))
+        })
+    }
+    /// The value of the table at `value`.
+    ///
+    /// This never allocates or panics, so it's safe to call from real time code. The
+    /// same goes for [`Piecewise::eval_indexed`], [`Piecewise::y_at_x_extrapolated`] and
+    /// [`Piecewise::y_at_xs_into`].
     pub fn y_at_x(&self, value: f64) -> Result<f64, PiecewiseErr> {
         let value = NotNan::new(value)?;
         let data = self.as_slice();
-        //since we know the domains have to be sorted (try_from will result Err if not)
-        //we can binary search the domains to find the domain needed
-        let bsearch = data.binary_search_by(|point| point.0.cmp(&value));
-
-        let index = match bsearch {
-            //checks to see if the value is out of out domains bound
-            Err(x) if x == 0 || x - 1 > data.len() => return Err(PiecewiseErr::NotInDomain),
-            //if not out of bounds then x is the index of the next point
-            //ie. (0,0), (100, 0) and we supply 50 x will be the index of (100, 0)
-            Err(x) => x,
-            //if bsearch returns Ok(x) it means we landed on an exact point,
-            //so we can return that value without doing any math
-            Ok(x) => return Ok(data[x].1.into_inner()),
+
+        let index = match locate(data, value)? {
+            //if we landed on an exact point we can return that value without doing any math
+            Position::Knot(x) => return Ok(data[x].1.into_inner()),
+            Position::Between(x) => x,
         };
 
-        let Coord(x1, y1) = data[index - 1];
-        let Coord(x2, y2) = data[index];
+        self.1[index - 1].interpolate_finite(data[index - 1], data[index], value)
+    }
 
-        let slope = (y1 - y2) / (x1 - x2);
+    /// Like [`Piecewise::y_at_x`], but also returns the index of the segment used, with
+    /// segment `i` running from knot `i` to knot `i + 1`.
+    ///
+    /// A value on a knot belongs to the segment to its right, past any repeats of that
+    /// knot, except for the last knot which belongs to the final segment. A single knot
+    /// table reports segment 0.
+    pub fn eval_indexed(&self, value: f64) -> Result<(f64, usize), PiecewiseErr> {
+        let value = NotNan::new(value)?;
+        let data = self.as_slice();
+        let last_segment = data.len().saturating_sub(2);
 
-        Ok((slope * (value - x1) + y1).into_inner())
+        match locate(data, value)? {
+            Position::Knot(x) => {
+                let knot = data.partition_point(|point| point.0 <= value) - 1;
+                Ok((data[x].1.into_inner(), knot.min(last_segment)))
+            }
+            Position::Between(x) => Ok((
+                self.1[x - 1].interpolate_finite(data[x - 1], data[x], value)?,
+                x - 1,
+            )),
+        }
     }
+
+    /// Like [`Piecewise::y_at_x`], but out of domain values are handled by `policy`
+    /// instead of erroring. Only a NaN `value` errors.
+    pub fn y_at_x_extrapolated(
+        &self,
+        value: f64,
+        policy: Extrapolate,
+    ) -> Result<f64, PiecewiseErr> {
+        let value = NotNan::new(value)?;
+        let data = self.as_slice();
+        let (first, last) = (data[0], data[data.len() - 1]);
+
+        if value >= first.0 && value <= last.0 {
+            return self.y_at_x(value.into_inner());
+        }
+
+        //the end segment, skipping over duplicates of the endpoint
+        let (end, segment) = if value < first.0 {
+            let inner = data.iter().position(|point| point.0 != first.0);
+            (first, inner.map(|i| (i - 1, data[i - 1], data[i])))
+        } else {
+            let inner = data.iter().rposition(|point| point.0 != last.0);
+            (last, inner.map(|i| (i, data[i], data[i + 1])))
+        };
+
+        match (policy, segment) {
+            (Extrapolate::Linear, Some((index, left, right)))
+                if !matches!(
+                    self.1[index],
+                    InterpKind::StepPrevious | InterpKind::StepNext
+                ) =>
+            {
+                self.1[index].interpolate_finite(left, right, value)
+            }
+            //a table with a single x value has no slope to continue and steps have
+            //no slope at all, so those hold like Clamp
+            _ => Ok(end.1.into_inner()),
+        }
+    }
+
+    /// Turns the table into a closure that clamps out of domain inputs to the endpoints.
+    ///
+    /// The closure never panics: a NaN input evaluates to NaN.
+    pub fn into_boxed_fn(self) -> Box<dyn Fn(f64) -> f64 + Send + Sync> {
+        self.into_boxed_fn_with(Extrapolate::Clamp)
+    }
+
+    /// Turns the table into a closure that handles out of domain inputs with `policy`.
+    ///
+    /// The closure never panics: a NaN input evaluates to NaN.
+    pub fn into_boxed_fn_with(self, policy: Extrapolate) -> Box<dyn Fn(f64) -> f64 + Send + Sync> {
+        Box::new(move |x| self.y_at_x_extrapolated(x, policy).unwrap_or(f64::NAN))
+    }
+
+    /// A hash of the table's contents that is stable across platforms and crate versions,
+    /// meant for cache keys.
+    ///
+    /// This is 64 bit FNV-1a over, in order and as little endian bytes: the knot count
+    /// as a `u64`, the IEEE-754 bits of each knot's x then y, and one byte per segment
+    /// for its [`InterpKind`] (0 Linear, 1 LogY, 2 LogLog, 3 StepPrevious). Bits are
+    /// hashed exactly, so `0.0` and `-0.0` fingerprint differently.
+    pub fn fingerprint(&self) -> u64 {
+        const OFFSET_BASIS: u64 = 0xcbf2_9ce4_8422_2325;
+        const PRIME: u64 = 0x0000_0100_0000_01b3;
+
+        let mut hash = OFFSET_BASIS;
+        let mut write = |bytes: &[u8]| {
+            for &byte in bytes {
+                hash ^= byte as u64;
+                hash = hash.wrapping_mul(PRIME);
+            }
+        };
+
+        write(&(self.0.len() as u64).to_le_bytes());
+        for point in self.as_slice() {
+            write(&point.x().to_bits().to_le_bytes());
+            write(&point.y().to_bits().to_le_bytes());
+        }
+        for kind in self.1.iter() {
+            write(&[match kind {
+                InterpKind::Linear => 0,
+                InterpKind::LogY => 1,
+                InterpKind::LogLog => 2,
+                InterpKind::StepPrevious => 3,
+                InterpKind::StepNext => 4,
+            }]);
+        }
+        hash
+    }
+}
+
+/// Where a value falls relative to the knots of a table.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum Position {
+    /// The value lands exactly on the knot at this index.
+    Knot(usize),
+    /// The value lies strictly between the knots at `index - 1` and `index`.
+    Between(usize),
+}
+
+pub(crate) fn locate(data: &[Coord], value: NotNan<f64>) -> Result<Position, PiecewiseErr> {
+    //since we know the domains have to be sorted (try_from will result Err if not)
+    //we can binary search the domains to find the domain needed
+    match data.binary_search_by(|point| point.0.cmp(&value)) {
+        Ok(x) => Ok(Position::Knot(x)),
+        //checks to see if the value is out of out domains bound
+        Err(x) if x == 0 || x == data.len() => Err(PiecewiseErr::NotInDomain),
+        //if not out of bounds then x is the index of the next point
+        //ie. (0,0), (100, 0) and we supply 50 x will be the index of (100, 0)
+        Err(x) => Ok(Position::Between(x)),
+    }
+}
+
+pub(crate) fn lerp(left: Coord, right: Coord, value: NotNan<f64>) -> f64 {
+    let (x1, y1) = (left.x(), left.y());
+    let (x2, y2) = (right.x(), right.y());
+    //flat segments are exact, even at an infinite y where the slope would be NaN
+    if y1 == y2 {
+        return y1;
+    }
+
+    //plain f64 rather than NotNan, which panics on a NaN result
+    let slope = (y1 - y2) / (x1 - x2);
+
+    slope * (value.into_inner() - x1) + y1
 }
 
 #[cfg(test)]
-mod tests {
+pub(crate) mod tests {
 
     use crate::Coord;
+    use crate::Extrapolate;
     use crate::Piecewise;
-    const SIDEARM: [Coord; 3] = unsafe {
+    pub(crate) const SIDEARM: [Coord; 3] = unsafe {
         [
             Coord::new_unchecked((0.0, 18.0)),
             Coord::new_unchecked((90.0, 36.0)),
@@ -132,7 +813,17 @@ mod tests {
         ]
     };
 
+    pub(crate) fn table(points: &[(f64, f64)]) -> Piecewise {
+        points
+            .iter()
+            .map(|&point| Coord::try_from(point).unwrap())
+            .collect::<Vec<_>>()
+            .try_into()
+            .unwrap()
+    }
+
     #[test]
+    #[allow(clippy::useless_conversion)]
     fn try_from() {
         let vec: Vec<Coord> = Vec::from(SIDEARM);
         let z: Piecewise = Piecewise::try_from(vec).unwrap();
@@ -141,6 +832,367 @@ mod tests {
         assert_eq!(z.y_at_x(93.0).unwrap().floor(), 37.0);
     }
 
+    #[test]
+    fn from_sorted_iter() {
+        use crate::{FromIterError, PiecewiseErr};
+
+        let sorted = SIDEARM.map(|point| Ok::<_, String>((point.x(), point.y())));
+        let z = Piecewise::from_sorted_iter(sorted).unwrap();
+        assert_eq!(z, Piecewise::try_from(Vec::from(SIDEARM)).unwrap());
+
+        let unsorted = [(0.0, 1.0), (2.0, 1.0), (1.0, 1.0)].map(Ok::<_, String>);
+        assert!(matches!(
+            Piecewise::from_sorted_iter(unsorted),
+            Err(FromIterError::Invalid {
+                index: 2,
+                error: PiecewiseErr::KnotsNotIncreasing { index: 2 }
+            })
+        ));
+
+        let lines = ["0 18", "90 36", "ninety five", "100 42"];
+        let parsed = lines.iter().map(|line| {
+            let (x, y) = line.split_once(' ').ok_or("no separator")?;
+            Ok((
+                x.parse::<f64>().map_err(|_| "bad x")?,
+                y.parse::<f64>().map_err(|_| "bad y")?,
+            ))
+        });
+        assert!(matches!(
+            Piecewise::from_sorted_iter(parsed),
+            Err(FromIterError::Source {
+                index: 2,
+                error: "bad x"
+            })
+        ));
+
+        let repeated = [(0.0, 1.0), (0.0, 1.0), (0.0, 2.0)].map(Ok::<_, String>);
+        assert!(matches!(
+            Piecewise::from_sorted_iter(repeated),
+            Err(FromIterError::Invalid { index: 2, .. })
+        ));
+        assert!(matches!(
+            Piecewise::from_sorted_iter(Vec::<Result<_, String>>::new()),
+            Err(FromIterError::InputEmpty)
+        ));
+    }
+
+    #[test]
+    fn undefined_reports_indices() {
+        use crate::PiecewiseErr;
+
+        let sorted = [(0.0, 1.0), (1.0, 2.0), (2.0, 3.0), (2.0, 4.0), (3.0, 5.0)];
+        let err = Piecewise::try_from(sorted.map(|p| Coord::try_from(p).unwrap()).to_vec());
+        assert!(matches!(
+            err,
+            Err(PiecewiseErr::InputUndefined {
+                x,
+                y_first,
+                y_second,
+                sorted_index: 2,
+                original_indices: (2, 3),
+            }) if x == 2.0 && y_first == 3.0 && y_second == 4.0
+        ));
+
+        let shuffled = [(3.0, 5.0), (2.0, 4.0), (0.0, 1.0), (2.0, 3.0), (1.0, 2.0)];
+        let err = Piecewise::try_from(shuffled.map(|p| Coord::try_from(p).unwrap()).to_vec());
+        assert!(matches!(
+            err,
+            Err(PiecewiseErr::InputUndefined {
+                x,
+                y_first,
+                y_second,
+                sorted_index: 2,
+                original_indices: (1, 3),
+            }) if x == 2.0 && y_first == 4.0 && y_second == 3.0
+        ));
+
+        let err = serde_json::from_str::<Piecewise>("[[3, 5], [2, 4], [0, 1], [2, 3], [1, 2]]")
+            .unwrap_err()
+            .to_string();
+        assert!(err.contains("x = 2"), "{err}");
+        assert!(err.contains("input indices 1 and 3"), "{err}");
+    }
+
+    #[test]
+    fn coord_from_str() {
+        use crate::CoordParseError;
+
+        let parsed = ["12.5,40", " 12.5 , 40 ", "(12.5, 40)", "( 12.5,40 )"]
+            .map(|s| s.parse::<Coord>().unwrap());
+        assert!(parsed
+            .iter()
+            .all(|&c| c == Coord::try_from((12.5, 40.0)).unwrap()));
+        assert_eq!(
+            "1e3,2.5e-1".parse::<Coord>().unwrap(),
+            Coord::try_from((1000.0, 0.25)).unwrap()
+        );
+
+        assert_eq!(
+            "1".parse::<Coord>(),
+            Err(CoordParseError::WrongFieldCount(1))
+        );
+        assert_eq!(
+            "1,2,3".parse::<Coord>(),
+            Err(CoordParseError::WrongFieldCount(3))
+        );
+        assert_eq!(
+            "1,two".parse::<Coord>(),
+            Err(CoordParseError::NotANumber("two".to_owned()))
+        );
+        assert_eq!(
+            "(1,2".parse::<Coord>(),
+            Err(CoordParseError::NotANumber("(1".to_owned()))
+        );
+        assert_eq!("NaN,2".parse::<Coord>(), Err(CoordParseError::NaN));
+    }
+
+    #[test]
+    fn coord_display_round_trip() {
+        let coords = [
+            (0.0, 18.0),
+            (-0.5, 1e300),
+            (0.1 + 0.2, -7.0),
+            (f64::MIN_POSITIVE, f64::INFINITY),
+            (123456.789, 1.0 / 3.0),
+        ];
+        for pair in coords {
+            let coord = Coord::try_from(pair).unwrap();
+            assert_eq!(coord.to_string().parse::<Coord>().unwrap(), coord);
+        }
+        assert_eq!(SIDEARM[1].to_string(), "90,36");
+    }
+
+    #[test]
+    fn non_finite_result() {
+        use crate::PiecewiseErr;
+
+        let steep = table(&[(0.0, -1e308), (1e-300, 1e308)]);
+        assert!(matches!(
+            steep.y_at_x(5e-301),
+            Err(PiecewiseErr::NonFiniteResult { x }) if x == 5e-301
+        ));
+        assert!(steep.eval_indexed(5e-301).is_err());
+        assert_eq!(steep.y_at_x(0.0).unwrap(), -1e308);
+        assert!(steep.y_at_x_extrapolated(1.0, Extrapolate::Linear).is_err());
+
+        //infinite knots are allowed to spread
+        let infinite = table(&[(0.0, 0.0), (1.0, f64::INFINITY)]);
+        assert_eq!(infinite.y_at_x(0.5).unwrap(), f64::INFINITY);
+    }
+
+    #[test]
+    fn evaluation_edge_cases_do_not_panic() {
+        let flat_infinite = table(&[(0.0, f64::INFINITY), (1.0, f64::INFINITY)]);
+        assert_eq!(flat_infinite.y_at_x(0.5).unwrap(), f64::INFINITY);
+        let opposite = table(&[(0.0, f64::NEG_INFINITY), (1.0, f64::INFINITY)]);
+        assert!(opposite.y_at_x(0.5).is_err());
+        assert!(opposite.y_at_xs(&[0.5; 100]).is_err());
+        let infinite_x = table(&[(f64::NEG_INFINITY, 0.0), (0.0, 1.0)]);
+        assert!(infinite_x.y_at_x(-1.0).is_err());
+        assert_eq!(infinite_x.y_at_x(f64::NEG_INFINITY).unwrap(), 0.0);
+
+        let z = Piecewise::try_from(Vec::from(SIDEARM)).unwrap();
+        assert_eq!(z.y_at_x(-0.0).unwrap(), 18.0);
+        for x in [
+            f64::INFINITY,
+            f64::NEG_INFINITY,
+            f64::MAX,
+            f64::MIN,
+            -f64::MIN_POSITIVE,
+            100.0f64.next_up(),
+        ] {
+            assert!(z.y_at_x(x).is_err(), "{x}");
+            assert!(z.eval_indexed(x).is_err(), "{x}");
+            assert!(z.y_at_xs(&[x; 100]).is_err(), "{x}");
+            let _ = z.y_at_x_extrapolated(x, Extrapolate::Linear);
+            assert!(z
+                .y_at_x_extrapolated(x, Extrapolate::Clamp)
+                .unwrap()
+                .is_finite());
+        }
+    }
+
+    #[test]
+    fn eval_indexed() {
+        let sidearm = Piecewise::try_from(Vec::from(SIDEARM)).unwrap();
+        assert_eq!(sidearm.eval_indexed(0.0).unwrap(), (18.0, 0));
+        assert_eq!(sidearm.eval_indexed(95.0).unwrap(), (39.0, 1));
+        assert_eq!(sidearm.eval_indexed(100.0).unwrap(), (42.0, 1));
+        assert!(sidearm.eval_indexed(100.5).is_err());
+        assert!(sidearm.eval_indexed(f64::NAN).is_err());
+
+        //walking across the knot at 90 switches segments once, right on the knot
+        let mut switches = Vec::new();
+        let mut previous = 0;
+        for i in 0..=1000 {
+            let x = i as f64 / 10.0;
+            let (y, segment) = sidearm.eval_indexed(x).unwrap();
+            assert_eq!(y, sidearm.y_at_x(x).unwrap());
+            if segment != previous {
+                switches.push(x);
+                previous = segment;
+            }
+        }
+        assert_eq!(switches, [90.0]);
+
+        let repeated = table(&[(0.0, 0.0), (1.0, 1.0), (1.0, 1.0), (2.0, 0.0)]);
+        assert_eq!(repeated.eval_indexed(1.0).unwrap(), (1.0, 2));
+        assert_eq!(table(&[(3.0, 4.0)]).eval_indexed(3.0).unwrap(), (4.0, 0));
+    }
+
+    #[test]
+    fn fingerprint() {
+        let z = Piecewise::try_from(Vec::from(SIDEARM)).unwrap();
+        //changing this means every cache keyed on fingerprints gets invalidated
+        assert_eq!(z.fingerprint(), 0xd565_3f85_cbb6_ccac);
+
+        let shuffled = Piecewise::try_from(vec![SIDEARM[2], SIDEARM[0], SIDEARM[1]]).unwrap();
+        assert_eq!(shuffled.fingerprint(), z.fingerprint());
+
+        let moved = table(&[(0.0, 18.0), (90.0, 36.0), (100.0, 42.5)]);
+        assert_ne!(moved.fingerprint(), z.fingerprint());
+        let stepped = z
+            .clone()
+            .with_interp(crate::InterpKind::StepPrevious)
+            .unwrap();
+        assert_ne!(stepped.fingerprint(), z.fingerprint());
+        assert_ne!(
+            table(&[(0.0, 0.0)]).fingerprint(),
+            table(&[(-0.0, 0.0)]).fingerprint()
+        );
+    }
+
+    #[test]
+    fn range() {
+        let z = table(&[(0.0, 5.0), (1.0, -2.0), (2.0, 7.0), (3.0, 1.0)]);
+        assert_eq!(z.range(), (-2.0, 7.0));
+        assert_eq!(table(&[(4.0, 3.0)]).range(), (3.0, 3.0));
+    }
+
+    #[test]
+    fn identity_and_line() {
+        use crate::PiecewiseErr;
+
+        let sidearm = Piecewise::try_from(Vec::from(SIDEARM)).unwrap();
+        let identity = Piecewise::identity(0.0, 100.0).unwrap();
+        assert_eq!(identity.compose(&sidearm).unwrap(), sidearm);
+
+        //the other way around restricts the table to the identity's domain
+        let restricted = sidearm
+            .compose(&Piecewise::identity(45.0, 95.0).unwrap())
+            .unwrap();
+        assert_eq!(restricted.domain(), (45.0, 95.0));
+        assert_eq!(
+            restricted,
+            table(&[(45.0, 27.0), (90.0, 36.0), (95.0, 39.0)])
+        );
+
+        assert!(matches!(
+            Piecewise::identity(1.0, 1.0),
+            Err(PiecewiseErr::InvalidInterval { min: 1.0, max: 1.0 })
+        ));
+        assert_eq!(
+            Piecewise::identity(2.0, 1.0).unwrap_err().to_string(),
+            "[2, 1] is not an interval with its min below its max"
+        );
+        assert!(Piecewise::identity(f64::NAN, 1.0).is_err());
+
+        let line = Piecewise::line((10.0, 40.0), (0.0, 20.0)).unwrap();
+        assert_eq!(line.y_at_x(5.0).unwrap(), 30.0);
+        assert_eq!(line.domain(), (0.0, 10.0));
+        assert!(matches!(
+            Piecewise::line((1.0, 1.0), (1.0, 2.0)),
+            Err(PiecewiseErr::InputUndefined { .. })
+        ));
+    }
+
+    #[test]
+    fn coord_order() {
+        let mut shuffled = vec![SIDEARM[2], SIDEARM[0], SIDEARM[1], SIDEARM[0]];
+        let table = Piecewise::try_from(shuffled.clone()).unwrap();
+        shuffled.sort();
+        assert_eq!(shuffled, table.as_slice());
+
+        let (low, high) = (
+            Coord::try_from((1.0, 5.0)).unwrap(),
+            Coord::try_from((1.0, 7.0)).unwrap(),
+        );
+        assert!(low < high);
+        assert_eq!(Coord::cmp_by_x(&low, &high), std::cmp::Ordering::Equal);
+        assert_eq!(
+            Coord::cmp_by_x(&SIDEARM[1], &low),
+            std::cmp::Ordering::Greater
+        );
+    }
+
+    #[test]
+    fn knot_index_at_or_before_and_after() {
+        let z = Piecewise::try_from(Vec::from(SIDEARM)).unwrap();
+        for (i, knot) in SIDEARM.iter().enumerate() {
+            let (below, above) = (knot.x() - 0.5, knot.x() + 0.5);
+            assert_eq!(z.knot_index_at_or_before(knot.x()).unwrap(), Some(i));
+            assert_eq!(z.knot_index_at_or_after(knot.x()).unwrap(), Some(i));
+            assert_eq!(z.knot_index_at_or_before(below).unwrap(), i.checked_sub(1));
+            assert_eq!(z.knot_index_at_or_after(below).unwrap(), Some(i));
+            assert_eq!(z.knot_index_at_or_before(above).unwrap(), Some(i));
+            assert_eq!(
+                z.knot_index_at_or_after(above).unwrap(),
+                (i + 1 < SIDEARM.len()).then_some(i + 1)
+            );
+        }
+        assert!(z.knot_index_at_or_before(f64::NAN).is_err());
+        assert!(z.knot_index_at_or_after(f64::NAN).is_err());
+
+        let repeated = table(&[(0.0, 0.0), (1.0, 1.0), (1.0, 1.0), (1.0, 1.0), (2.0, 0.0)]);
+        assert_eq!(repeated.knot_index_at_or_before(1.0).unwrap(), Some(3));
+        assert_eq!(repeated.knot_index_at_or_after(1.0).unwrap(), Some(1));
+    }
+
+    #[test]
+    fn insertion_index() {
+        let z = Piecewise::try_from(Vec::from(SIDEARM)).unwrap();
+        assert_eq!(z.insertion_index(-5.0), 0);
+        assert_eq!(z.insertion_index(0.0), 0);
+        assert_eq!(z.insertion_index(50.0), 1);
+        assert_eq!(z.insertion_index(90.0), 1);
+        assert_eq!(z.insertion_index(90.5), 2);
+        assert_eq!(z.insertion_index(1000.0), 3);
+        assert_eq!(z.insertion_index(f64::NAN), 0);
+
+        let mut points = Vec::from(SIDEARM);
+        let knot = Coord::try_from((95.0, 40.0)).unwrap();
+        points.insert(z.insertion_index(knot.x()), knot);
+        assert!(points.is_sorted_by(|a, b| Coord::cmp_by_x(a, b).is_le()));
+    }
+
+    #[test]
+    fn clones_share_until_written() {
+        let original = Piecewise::try_from(Vec::from(SIDEARM)).unwrap();
+        let mut copy = original.clone();
+        assert!(std::ptr::eq(original.points(), copy.points()));
+
+        copy.insert_knot(45.0).unwrap();
+        assert!(!std::ptr::eq(original.points(), copy.points()));
+        assert_eq!(original.points(), SIDEARM);
+        assert_eq!(copy.points().len(), 4);
+
+        let mut stepped = original.clone();
+        stepped
+            .set_segment_kind(0, crate::InterpKind::StepPrevious)
+            .unwrap();
+        assert_eq!(original.segment_kinds(), [crate::InterpKind::Linear; 2]);
+        assert_eq!(original.y_at_x(45.0).unwrap(), 27.0);
+        assert_eq!(stepped.y_at_x(45.0).unwrap(), 18.0);
+    }
+
+    #[test]
+    fn out_of_domain() {
+        let z: Piecewise = Piecewise::try_from(Vec::from(SIDEARM)).unwrap();
+        assert!(z.y_at_x(-1.0).is_err());
+        assert!(z.y_at_x(101.0).is_err());
+        assert!(z.y_at_x(f64::NAN).is_err());
+    }
+
     #[test]
     fn serialize() {
         let vec: Vec<Coord> = Vec::from(SIDEARM);
@@ -158,4 +1210,98 @@ mod tests {
             "[[0.0,18.0],[90.0,36.0],[100.0,42.0]]"
         );
     }
+
+    #[test]
+    fn deserialize_map() {
+        let z: Piecewise = serde_json::from_str(r#"{"0": 18, "90": 36, "100": 42}"#).unwrap();
+        assert_eq!(
+            serde_json::to_string(&z).unwrap(),
+            "[[0.0,18.0],[90.0,36.0],[100.0,42.0]]"
+        );
+
+        let z: Piecewise = serde_json::from_str(r#"{"12.5": 1.0, "0.25": 2, "1e2": 3}"#).unwrap();
+        assert_eq!(
+            serde_json::to_string(&z).unwrap(),
+            "[[0.25,2.0],[12.5,1.0],[100.0,3.0]]"
+        );
+    }
+
+    #[test]
+    fn deserialize_map_bad_key() {
+        let err = serde_json::from_str::<Piecewise>(r#"{"0": 18, "abc": 36}"#).unwrap_err();
+        assert!(err.to_string().contains(r#"the key "abc" is not a number"#));
+
+        let err = serde_json::from_str::<Piecewise>(r#"{"NaN": 18}"#).unwrap_err();
+        assert!(err.to_string().contains("is NaN"));
+    }
+
+    #[test]
+    fn deserialize_rejects_infinity() {
+        use serde::de::value::Error;
+        use serde::de::IntoDeserializer;
+        use serde::Deserialize;
+
+        //serde_json can't produce infinite values in arrays, other formats can
+        fn from_pairs<T: for<'de> Deserialize<'de>>(pairs: &[[f64; 2]]) -> Result<T, Error> {
+            let pairs = pairs.iter().map(|pair| pair.to_vec()).collect::<Vec<_>>();
+            T::deserialize(pairs.into_deserializer())
+        }
+
+        let coord = |pair: [f64; 2]| Coord::deserialize(pair.to_vec().into_deserializer());
+        assert_eq!(coord([90.0, 36.0]).unwrap(), SIDEARM[1]);
+        let err: Error = coord([f64::INFINITY, 1.0]).unwrap_err();
+        assert_eq!(err.to_string(), "the x value inf is not finite");
+        let err: Error = coord([1.0, f64::NEG_INFINITY]).unwrap_err();
+        assert_eq!(err.to_string(), "the y value -inf is not finite");
+
+        let err = from_pairs::<Piecewise>(&[[0.0, 18.0], [90.0, 36.0], [100.0, f64::INFINITY]])
+            .unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "the knot at index 2 is invalid: the y value inf is not finite"
+        );
+        let err = from_pairs::<crate::PiecewiseAuthored>(&[[f64::NEG_INFINITY, 0.0]]).unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "the knot at index 0 is invalid: the x value -inf is not finite"
+        );
+        let z = from_pairs::<Piecewise>(&[[0.0, 18.0], [90.0, 36.0], [100.0, 42.0]]).unwrap();
+        assert_eq!(z, Piecewise::try_from(Vec::from(SIDEARM)).unwrap());
+
+        let err = serde_json::from_str::<Piecewise>(r#"{"0": 18, "inf": 36}"#).unwrap_err();
+        assert!(
+            err.to_string()
+                .contains("x = inf is invalid: the x value inf is not finite"),
+            "{err}"
+        );
+        let err = serde_json::from_str::<Piecewise>(r#"{"0": 18, "-inf": 36}"#).unwrap_err();
+        assert!(
+            err.to_string().contains("the x value -inf is not finite"),
+            "{err}"
+        );
+        //overflowing literals are already refused by serde_json itself
+        assert!(serde_json::from_str::<Piecewise>("[[0, 18], [90, 1e999]]").is_err());
+        assert!(serde_json::from_str::<Coord>("[0, -1e999]").is_err());
+
+        let z: Piecewise = serde_json::from_str("[[0, 18], [90, 36], [100, 1e300]]").unwrap();
+        assert_eq!(z.range(), (18.0, 1e300));
+    }
+
+    #[test]
+    fn boxed_fn() {
+        let z: Piecewise = Piecewise::try_from(Vec::from(SIDEARM)).unwrap();
+        let curves: Vec<Box<dyn Fn(f64) -> f64 + Send + Sync>> = vec![
+            z.clone().into_boxed_fn(),
+            z.into_boxed_fn_with(Extrapolate::Linear),
+            Box::new(|x| x * 2.0),
+        ];
+
+        let at_90: Vec<f64> = curves.iter().map(|f| f(90.0)).collect();
+        assert_eq!(at_90, [36.0, 36.0, 180.0]);
+        let at_110: Vec<f64> = curves.iter().map(|f| f(110.0)).collect();
+        assert_eq!(at_110, [42.0, 48.0, 220.0]);
+        assert_eq!(curves[0](-10.0), 18.0);
+        assert_eq!(curves[1](-10.0), 16.0);
+        assert!(curves[0](f64::NAN).is_nan());
+    }
 }
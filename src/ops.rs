@@ -0,0 +1,492 @@
+use std::cmp::Ordering;
+
+use ordered_float::NotNan;
+
+use crate::{Coord, Piecewise, PiecewiseErr};
+
+impl Piecewise {
+    /// The part of the x axis covered by both tables.
+    pub(crate) fn shared_domain(&self, other: &Piecewise) -> Result<(f64, f64), PiecewiseErr> {
+        let (min_a, max_a) = self.domain();
+        let (min_b, max_b) = other.domain();
+        let (low, high) = (min_a.max(min_b), max_a.min(max_b));
+        if low > high {
+            return Err(PiecewiseErr::DisjointDomains);
+        }
+        Ok((low, high))
+    }
+
+    /// Every knot x of either table within the shared domain, sorted and deduplicated,
+    /// always including both ends of the shared domain.
+    pub(crate) fn union_knots(&self, other: &Piecewise) -> Result<Vec<f64>, PiecewiseErr> {
+        let (low, high) = self.shared_domain(other)?;
+        let mut knots: Vec<f64> = self
+            .as_slice()
+            .iter()
+            .chain(other.as_slice())
+            .map(|point| point.0.into_inner())
+            .filter(|&x| x > low && x < high)
+            .chain([low, high])
+            .collect();
+        knots.sort_by(f64::total_cmp);
+        knots.dedup();
+        Ok(knots)
+    }
+
+    /// `self - other` as `(x, difference)` points over the shared domain.
+    ///
+    /// The difference is linear between consecutive points, and a point is added
+    /// wherever it crosses zero, so every piece between two points has a single sign.
+    /// That only holds for linear tables, so other kinds error.
+    pub(crate) fn difference(&self, other: &Piecewise) -> Result<Vec<(f64, f64)>, PiecewiseErr> {
+        self.check_linear()?;
+        other.check_linear()?;
+        let knots = self.union_knots(other)?;
+        let mut points: Vec<(f64, f64)> = Vec::with_capacity(knots.len());
+        for x in knots {
+            let d = self.y_at_x(x)? - other.y_at_x(x)?;
+            if let Some(&(x0, d0)) = points.last() {
+                if (d0 < 0.0 && d > 0.0) || (d0 > 0.0 && d < 0.0) {
+                    points.push((x0 + (x - x0) * d0 / (d0 - d), 0.0));
+                }
+            }
+            points.push((x, d));
+        }
+        Ok(points)
+    }
+
+    /// The exact integral of `self - other` over the shared domain.
+    pub fn area_between(&self, other: &Piecewise) -> Result<f64, PiecewiseErr> {
+        let points = self.difference(other)?;
+        Ok(points
+            .windows(2)
+            .map(|pair| (pair[1].0 - pair[0].0) * (pair[0].1 + pair[1].1) / 2.0)
+            .sum())
+    }
+
+    /// The exact integral of `|self - other|` over the shared domain.
+    pub fn abs_area_between(&self, other: &Piecewise) -> Result<f64, PiecewiseErr> {
+        let points = self.difference(other)?;
+        Ok(points
+            .windows(2)
+            .map(|pair| ((pair[1].0 - pair[0].0) * (pair[0].1 + pair[1].1) / 2.0).abs())
+            .sum())
+    }
+
+    /// The largest `|self - other|` over the shared domain.
+    ///
+    /// The difference is piecewise linear, so this is exact: it's reached at a knot of one table.
+    pub fn max_abs_difference(&self, other: &Piecewise) -> Result<f64, PiecewiseErr> {
+        let points = self.difference(other)?;
+        Ok(points.iter().map(|point| point.1.abs()).fold(0.0, f64::max))
+    }
+
+    /// The L2 distance, `sqrt(∫(self - other)²)`, over the shared domain.
+    ///
+    /// Each piece of the difference is linear, so its square integrates exactly.
+    pub fn l2_distance(&self, other: &Piecewise) -> Result<f64, PiecewiseErr> {
+        let points = self.difference(other)?;
+        let integral: f64 = points
+            .windows(2)
+            .map(|pair| {
+                let ((x0, d0), (x1, d1)) = (pair[0], pair[1]);
+                (x1 - x0) * (d0 * d0 + d0 * d1 + d1 * d1) / 3.0
+            })
+            .sum();
+        Ok(integral.sqrt())
+    }
+
+    /// Splits the shared domain into the intervals `(start, end, ordering)` where `self`
+    /// is above, below or equal to `other`. Both tables have to be linear.
+    ///
+    /// Neighbouring intervals always differ in ordering. A point where the tables only
+    /// touch is merged into the intervals around it rather than reported on its own, so
+    /// the only zero-width interval is the whole shared domain when it's a single x.
+    pub fn compare_regions(
+        &self,
+        other: &Piecewise,
+    ) -> Result<Vec<(f64, f64, Ordering)>, PiecewiseErr> {
+        let points = self.difference(other)?;
+        if let [(x, d)] = points[..] {
+            return Ok(vec![(x, x, d.total_cmp(&0.0))]);
+        }
+
+        let mut regions: Vec<(f64, f64, Ordering)> = Vec::new();
+        for pair in points.windows(2) {
+            let ((x0, d0), (x1, d1)) = (pair[0], pair[1]);
+            //each piece has a single sign, so its middle is representative
+            let ordering = (d0 + d1).partial_cmp(&0.0).unwrap_or(Ordering::Equal);
+            match regions.last_mut() {
+                Some(last) if last.2 == ordering => last.1 = x1,
+                _ => regions.push((x0, x1, ordering)),
+            }
+        }
+        Ok(regions)
+    }
+
+    /// `(1 - t) * self + t * other` over the shared domain, with the knots of both
+    /// tables.
+    ///
+    /// A `t` of 0 or 1 gives the values of `self` or `other` exactly, and values
+    /// outside `[0, 1]` extrapolate. Only linear tables blend into a table, other kinds
+    /// error with [`PiecewiseErr::UnsupportedInterp`].
+    pub fn blend(&self, other: &Piecewise, t: f64) -> Result<Piecewise, PiecewiseErr> {
+        let t = NotNan::new(t)?.into_inner();
+        self.check_linear()?;
+        other.check_linear()?;
+        let points = self
+            .union_knots(other)?
+            .into_iter()
+            .map(|x| {
+                let y = (1.0 - t) * self.y_at_x(x)? + t * other.y_at_x(x)?;
+                Ok(Coord::try_from((x, y))?)
+            })
+            .collect::<Result<Vec<_>, PiecewiseErr>>()?;
+        Piecewise::try_from(points)
+    }
+
+    /// The lower of the two tables at every x of their shared domain.
+    ///
+    /// The knots are those of both tables plus every point where they cross. Both
+    /// tables have to be linear, as for all exact geometry between tables.
+    pub fn pointwise_min(&self, other: &Piecewise) -> Result<Piecewise, PiecewiseErr> {
+        self.pointwise(other, f64::min)
+    }
+
+    /// The higher of the two tables at every x of their shared domain, with the same
+    /// knots as [`Piecewise::pointwise_min`].
+    pub fn pointwise_max(&self, other: &Piecewise) -> Result<Piecewise, PiecewiseErr> {
+        self.pointwise(other, f64::max)
+    }
+
+    fn pointwise(
+        &self,
+        other: &Piecewise,
+        pick: fn(f64, f64) -> f64,
+    ) -> Result<Piecewise, PiecewiseErr> {
+        let points = self
+            .difference(other)?
+            .into_iter()
+            .map(|(x, _)| {
+                let y = pick(self.y_at_x(x)?, other.y_at_x(x)?);
+                Ok(Coord::try_from((x, y))?)
+            })
+            .collect::<Result<Vec<_>, PiecewiseErr>>()?;
+        Piecewise::try_from(points)
+    }
+
+    /// `self(inner(x))` over the domain of `inner`, which must stay within the domain
+    /// of `self`.
+    ///
+    /// The knots are those of `inner` plus every x where `inner` reaches a knot x of
+    /// `self`, and the result is linear between them. That's only exact when both
+    /// tables are linear, so other kinds error with [`PiecewiseErr::UnsupportedInterp`].
+    pub fn compose(&self, inner: &Piecewise) -> Result<Piecewise, PiecewiseErr> {
+        self.check_linear()?;
+        inner.check_linear()?;
+        let (min, max) = self.domain();
+        let (low, high) = inner.range();
+        if low < min || high > max {
+            return Err(PiecewiseErr::NotInDomain);
+        }
+        self.composed(inner)
+    }
+
+    /// Like [`Piecewise::compose`], but values of `inner` outside the domain of `self`
+    /// are clamped to its ends instead of erroring.
+    ///
+    /// The knots also include every x where `inner` leaves or re-enters the domain, so
+    /// the clamped stretches are flat at the endpoint values of `self`. Like
+    /// [`Piecewise::compose`], both tables have to be linear.
+    pub fn compose_clamped(&self, inner: &Piecewise) -> Result<Piecewise, PiecewiseErr> {
+        self.check_linear()?;
+        inner.check_linear()?;
+        self.composed(inner)
+    }
+
+    /// [`Piecewise::compose`] once the domain has been checked, or deliberately not.
+    /// Values of `inner` outside the domain of `self` are clamped to its ends.
+    pub(crate) fn composed(&self, inner: &Piecewise) -> Result<Piecewise, PiecewiseErr> {
+        let (min, max) = self.domain();
+        let outer_xs = self
+            .as_slice()
+            .iter()
+            .map(|point| point.x())
+            .collect::<Vec<_>>();
+        let data = inner.as_slice();
+
+        let mut xs = vec![data[0].x()];
+        for (pair, kind) in data.windows(2).zip(inner.1.iter()) {
+            let (x1, y1, x2, y2) = (pair[0].x(), pair[0].y(), pair[1].x(), pair[1].y());
+            let (low, high) = (y1.min(y2), y1.max(y2));
+            let mut crossings = outer_xs
+                .iter()
+                .filter(|&&u| low < u && u < high)
+                .map(|&u| kind.crossing(x1, y1, x2, y2, u).clamp(x1, x2))
+                .collect::<Vec<_>>();
+            crossings.sort_by(f64::total_cmp);
+            xs.extend(crossings);
+            xs.push(x2);
+        }
+
+        let points = xs
+            .into_iter()
+            .map(|x| {
+                //clamping also absorbs rounding just past the ends
+                let u = inner.y_at_x(x)?.clamp(min, max);
+                Ok(Coord::try_from((x, self.y_at_x(u)?))?)
+            })
+            .collect::<Result<Vec<_>, PiecewiseErr>>()?;
+        Piecewise::try_from(points)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::cmp::Ordering;
+
+    use crate::tests::{table, SIDEARM};
+    use crate::{InterpKind, Piecewise, PiecewiseErr};
+
+    #[test]
+    fn area_between_triangles() {
+        let triangle = table(&[(0.0, 0.0), (1.0, 1.0), (2.0, 0.0)]);
+        let zero = table(&[(-1.0, 0.0), (3.0, 0.0)]);
+        assert_eq!(triangle.area_between(&zero).unwrap(), 1.0);
+        assert_eq!(zero.area_between(&triangle).unwrap(), -1.0);
+        assert_eq!(zero.abs_area_between(&triangle).unwrap(), 1.0);
+
+        let shifted = table(&[(1.0, 0.0), (2.0, 1.0), (3.0, 0.0)]);
+        //over [1, 2] the difference falls linearly from 1 to -1
+        assert_eq!(triangle.area_between(&shifted).unwrap(), 0.0);
+        assert_eq!(triangle.abs_area_between(&shifted).unwrap(), 0.5);
+    }
+
+    #[test]
+    fn area_between_crossing() {
+        let falling = table(&[(0.0, 1.0), (2.0, -1.0)]);
+        let rising = table(&[(0.0, -1.0), (2.0, 1.0)]);
+        assert_eq!(falling.area_between(&rising).unwrap(), 0.0);
+        assert_eq!(falling.abs_area_between(&rising).unwrap(), 2.0);
+    }
+
+    #[test]
+    fn area_between_disjoint() {
+        let left = table(&[(0.0, 1.0), (1.0, 1.0)]);
+        let right = table(&[(2.0, 1.0), (3.0, 1.0)]);
+        assert!(matches!(
+            left.area_between(&right),
+            Err(PiecewiseErr::DisjointDomains)
+        ));
+    }
+
+    #[test]
+    fn distance_to_shifted_copy() {
+        let original = Piecewise::try_from(Vec::from(SIDEARM)).unwrap();
+        let shifted = table(&[(0.0, 20.0), (90.0, 38.0), (100.0, 44.0)]);
+        assert_eq!(original.max_abs_difference(&shifted).unwrap(), 2.0);
+        //sqrt(2² * 100)
+        assert_eq!(original.l2_distance(&shifted).unwrap(), 20.0);
+
+        let partial = table(&[(50.0, 0.0), (200.0, 0.0)]);
+        assert_eq!(original.max_abs_difference(&partial).unwrap(), 42.0);
+    }
+
+    #[test]
+    fn distance_of_crossing_lines() {
+        let falling = table(&[(0.0, 1.0), (2.0, -1.0)]);
+        let rising = table(&[(0.0, -1.0), (2.0, 1.0)]);
+        assert_eq!(falling.max_abs_difference(&rising).unwrap(), 2.0);
+        //∫₀² (2 - 2x)² dx = 8/3
+        let expected = (8.0f64 / 3.0).sqrt();
+        assert!((falling.l2_distance(&rising).unwrap() - expected).abs() < 1e-12);
+        assert!(falling.l2_distance(&table(&[(3.0, 0.0)])).is_err());
+    }
+
+    #[test]
+    fn blend() {
+        let sidearm = Piecewise::try_from(Vec::from(SIDEARM)).unwrap();
+        let flat = table(&[(0.0, 20.0), (50.0, 20.0), (100.0, 20.0)]);
+        assert_eq!(
+            sidearm.blend(&flat, 0.5).unwrap(),
+            table(&[(0.0, 19.0), (50.0, 24.0), (90.0, 28.0), (100.0, 31.0)])
+        );
+        let start = sidearm.blend(&flat, 0.0).unwrap();
+        for x in [0.0, 50.0, 90.0, 95.0] {
+            assert_eq!(start.y_at_x(x).unwrap(), sidearm.y_at_x(x).unwrap());
+        }
+        assert!(sidearm.blend(&flat, f64::NAN).is_err());
+        assert!(matches!(
+            sidearm.blend(&table(&[(200.0, 0.0)]), 0.5),
+            Err(PiecewiseErr::DisjointDomains)
+        ));
+    }
+
+    #[test]
+    fn compare_regions() {
+        let zero = table(&[(0.0, 0.0), (10.0, 0.0)]);
+        //the difference goes 1, -1, 1, 0, 1 at x = 0, 2, 4, 6, 8, touching at 6
+        let wavy = table(&[
+            (0.0, -1.0),
+            (2.0, 1.0),
+            (4.0, -1.0),
+            (6.0, 0.0),
+            (8.0, -1.0),
+        ]);
+        assert_eq!(
+            zero.compare_regions(&wavy).unwrap(),
+            [
+                (0.0, 1.0, Ordering::Greater),
+                (1.0, 3.0, Ordering::Less),
+                (3.0, 8.0, Ordering::Greater),
+            ]
+        );
+
+        let flat_then_rising = table(&[(0.0, 0.0), (4.0, 0.0), (6.0, 2.0), (10.0, -2.0)]);
+        assert_eq!(
+            flat_then_rising.compare_regions(&zero).unwrap(),
+            [
+                (0.0, 4.0, Ordering::Equal),
+                (4.0, 8.0, Ordering::Greater),
+                (8.0, 10.0, Ordering::Less),
+            ]
+        );
+
+        let touching = table(&[(10.0, 3.0), (12.0, 3.0)]);
+        assert_eq!(
+            zero.compare_regions(&touching).unwrap(),
+            [(10.0, 10.0, Ordering::Less)]
+        );
+        assert!(matches!(
+            zero.compare_regions(&table(&[(11.0, 0.0)])),
+            Err(PiecewiseErr::DisjointDomains)
+        ));
+    }
+
+    #[test]
+    fn compose() {
+        //doubles and then squares-ish: 0 -> 0, 1 -> 1, 2 -> 4
+        let outer = table(&[(0.0, 0.0), (1.0, 1.0), (2.0, 4.0)]);
+        let inner = table(&[(0.0, 0.0), (4.0, 2.0)]);
+        let composed = outer.compose(&inner).unwrap();
+        assert_eq!(composed, table(&[(0.0, 0.0), (2.0, 1.0), (4.0, 4.0)]));
+        for x in [0.0, 0.5, 1.0, 2.5, 3.0, 4.0] {
+            let expected = outer.y_at_x(inner.y_at_x(x).unwrap()).unwrap();
+            assert!((composed.y_at_x(x).unwrap() - expected).abs() < 1e-12);
+        }
+
+        //falling through the outer knots the other way
+        let falling = table(&[(0.0, 2.0), (4.0, 0.0)]);
+        assert_eq!(
+            outer.compose(&falling).unwrap(),
+            table(&[(0.0, 4.0), (2.0, 1.0), (4.0, 0.0)])
+        );
+
+        let overshoot = table(&[(0.0, 0.0), (1.0, 2.5)]);
+        assert!(matches!(
+            outer.compose(&overshoot),
+            Err(PiecewiseErr::NotInDomain)
+        ));
+        let sidearm = Piecewise::try_from(Vec::from(SIDEARM)).unwrap();
+        assert!(sidearm.compose(&outer).is_ok());
+
+        //a step inner table would have its jumps ramped, and log kinds bent straight
+        let stepped = inner.with_interp(InterpKind::StepPrevious).unwrap();
+        assert!(matches!(
+            outer.compose(&stepped),
+            Err(PiecewiseErr::UnsupportedInterp(InterpKind::StepPrevious))
+        ));
+        let curved = sidearm.with_interp(InterpKind::LogY).unwrap();
+        assert!(matches!(
+            curved.compose(&outer),
+            Err(PiecewiseErr::UnsupportedInterp(InterpKind::LogY))
+        ));
+    }
+
+    #[test]
+    fn compose_clamped() {
+        let outer = table(&[(0.0, 10.0), (0.5, 15.0), (1.0, 30.0)]);
+        //authoring slop carries the inner curve a little past both ends
+        let inner = table(&[(0.0, -0.02), (1.0, 0.5), (2.0, 1.02)]);
+        assert!(matches!(
+            outer.compose(&inner),
+            Err(PiecewiseErr::NotInDomain)
+        ));
+
+        let composed = outer.compose_clamped(&inner).unwrap();
+        assert_eq!(composed.domain(), (0.0, 2.0));
+        assert_eq!(composed.y_at_x(0.0).unwrap(), 10.0);
+        assert_eq!(composed.y_at_x(2.0).unwrap(), 30.0);
+        assert_eq!(composed.y_at_x(1.0).unwrap(), 15.0);
+        for x in [0.01, 0.02, 1.97, 1.99] {
+            let (y, u) = (composed.y_at_x(x).unwrap(), inner.y_at_x(x).unwrap());
+            if !(0.0..=1.0).contains(&u) {
+                assert_eq!(y, outer.y_at_x(u.clamp(0.0, 1.0)).unwrap(), "{x}");
+            }
+        }
+        for x in [0.5, 1.5, 1.9] {
+            let expected = outer.y_at_x(inner.y_at_x(x).unwrap()).unwrap();
+            assert!((composed.y_at_x(x).unwrap() - expected).abs() < 1e-9, "{x}");
+        }
+
+        let in_range = table(&[(0.0, 0.0), (4.0, 1.0)]);
+        assert_eq!(
+            outer.compose_clamped(&in_range).unwrap(),
+            outer.compose(&in_range).unwrap()
+        );
+
+        let stepped = inner.clone().with_interp(InterpKind::StepPrevious).unwrap();
+        assert!(matches!(
+            outer.compose_clamped(&stepped),
+            Err(PiecewiseErr::UnsupportedInterp(InterpKind::StepPrevious))
+        ));
+        let curved = outer.with_interp(InterpKind::LogY).unwrap();
+        assert!(matches!(
+            curved.compose_clamped(&inner),
+            Err(PiecewiseErr::UnsupportedInterp(InterpKind::LogY))
+        ));
+    }
+
+    #[test]
+    fn pointwise_min_max() {
+        //a line crossing a hump twice, at x = 1 and x = 3
+        let hump = table(&[(0.0, 0.0), (2.0, 4.0), (4.0, 0.0)]);
+        let line = table(&[(-1.0, 2.0), (5.0, 2.0)]);
+        let min = hump.pointwise_min(&line).unwrap();
+        let max = hump.pointwise_max(&line).unwrap();
+        assert_eq!(
+            min,
+            table(&[(0.0, 0.0), (1.0, 2.0), (2.0, 2.0), (3.0, 2.0), (4.0, 0.0)])
+        );
+        assert_eq!(min.domain(), (0.0, 4.0));
+
+        //a small LCG so the probes are spread out but repeatable
+        let mut state: u64 = 0x2545_f491_4f6c_dd1d;
+        for _ in 0..1000 {
+            state = state.wrapping_mul(6364136223846793005).wrapping_add(1);
+            let x = (state >> 11) as f64 / (1u64 << 53) as f64 * 4.0;
+            let (a, b) = (hump.y_at_x(x).unwrap(), line.y_at_x(x).unwrap());
+            assert!((min.y_at_x(x).unwrap() - a.min(b)).abs() < 1e-12, "{x}");
+            assert!((max.y_at_x(x).unwrap() - a.max(b)).abs() < 1e-12, "{x}");
+        }
+
+        let far = table(&[(10.0, 0.0), (11.0, 0.0)]);
+        assert!(matches!(
+            hump.pointwise_min(&far),
+            Err(PiecewiseErr::DisjointDomains)
+        ));
+
+        let stepped = line.with_interp(InterpKind::StepPrevious).unwrap();
+        assert!(matches!(
+            hump.pointwise_max(&stepped),
+            Err(PiecewiseErr::UnsupportedInterp(InterpKind::StepPrevious))
+        ));
+        assert!(matches!(
+            stepped.compare_regions(&hump),
+            Err(PiecewiseErr::UnsupportedInterp(InterpKind::StepPrevious))
+        ));
+        assert!(hump.area_between(&stepped).is_err());
+        assert!(hump.l2_distance(&stepped).is_err());
+        assert!(hump.max_abs_difference(&stepped).is_err());
+    }
+}
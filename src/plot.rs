@@ -0,0 +1,112 @@
+use crate::Piecewise;
+
+impl Piecewise {
+    /// Renders the curve as a `width` by `height` character plot for debugging.
+    ///
+    /// Every column is sampled once, columns holding a knot are marked `o` and the
+    /// rest `*`. The y axis is labeled with the range and the x axis with the domain.
+    /// Sizes below 1 are treated as 1; a constant table is drawn along the middle row.
+    pub fn ascii_plot(&self, width: usize, height: usize) -> String {
+        let (width, height) = (width.max(1), height.max(1));
+        let data = self.as_slice();
+        let (min_x, max_x) = self.domain();
+        let min_y = data.iter().map(|p| p.1).min().unwrap().into_inner();
+        let max_y = data.iter().map(|p| p.1).max().unwrap().into_inner();
+
+        let x_span = max_x - min_x;
+        let column_of = |x: f64| match width {
+            1 => 0,
+            _ if x_span == 0.0 => 0,
+            _ => (((x - min_x) / x_span) * (width - 1) as f64).round() as usize,
+        };
+        let row_of = |y: f64| match height {
+            1 => 0,
+            _ if max_y == min_y => height / 2,
+            _ => (((max_y - y) / (max_y - min_y)) * (height - 1) as f64).round() as usize,
+        };
+
+        let mut grid = vec![vec![' '; width]; height];
+        let mut knot_columns = vec![false; width];
+        for point in data {
+            knot_columns[column_of(point.0.into_inner())] = true;
+        }
+        for (column, is_knot) in knot_columns.into_iter().enumerate() {
+            let x = match width {
+                1 => min_x,
+                _ => min_x + x_span * column as f64 / (width - 1) as f64,
+            };
+            //the sample can only fail by landing a hair outside the domain through rounding
+            let y = self.y_at_x(x.clamp(min_x, max_x)).unwrap_or(min_y);
+            grid[row_of(y)][column] = if is_knot { 'o' } else { '*' };
+        }
+
+        let (top, bottom) = (max_y.to_string(), min_y.to_string());
+        let label_width = top.len().max(bottom.len());
+        let mut out = String::new();
+        for (index, row) in grid.into_iter().enumerate() {
+            //a constant table only gets its one value labeled, next to the curve
+            let label = if max_y == min_y {
+                if index == row_of(min_y) {
+                    &top
+                } else {
+                    ""
+                }
+            } else if index == 0 {
+                &top
+            } else if index == height - 1 {
+                &bottom
+            } else {
+                ""
+            };
+            let row: String = row.into_iter().collect();
+            out.push_str(&format!("{label:>label_width$} |{}\n", row.trim_end()));
+        }
+        out.push_str(&format!("{:label_width$} +{}\n", "", "-".repeat(width)));
+
+        let (left, right) = (min_x.to_string(), max_x.to_string());
+        let gap = width.saturating_sub(left.len() + right.len()).max(1);
+        out.push_str(&format!("{:label_width$}  {left}{:gap$}{right}\n", "", ""));
+        out
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::tests::{table, SIDEARM};
+    use crate::Piecewise;
+
+    #[test]
+    fn plot_sidearm() {
+        let plot = Piecewise::try_from(Vec::from(SIDEARM))
+            .unwrap()
+            .ascii_plot(21, 5);
+        let lines: Vec<&str> = plot.lines().collect();
+        assert_eq!(lines.len(), 7);
+        assert_eq!(
+            plot,
+            "\
+42 |                    o
+   |                **o*
+   |          ******
+   |    ******
+18 |o***
+   +---------------------
+    0                 100
+"
+        );
+    }
+
+    #[test]
+    fn plot_degenerate() {
+        let constant = table(&[(0.0, 1.0), (10.0, 1.0)]).ascii_plot(5, 3);
+        assert_eq!(constant.lines().nth(1).unwrap(), "1 |o***o");
+
+        let single = table(&[(3.0, 4.0)]).ascii_plot(4, 1);
+        assert_eq!(single.lines().next().unwrap(), "4 |o***");
+
+        let tiny = Piecewise::try_from(Vec::from(SIDEARM))
+            .unwrap()
+            .ascii_plot(0, 0);
+        assert_eq!(tiny.lines().count(), 3);
+    }
+}
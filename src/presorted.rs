@@ -0,0 +1,121 @@
+//! Serde representation for large tables that are stored already sorted.
+//!
+//! Use it with `#[serde(with = "lerp_table::presorted")]`. The format is the plain
+//! array of `[x, y]` pairs, but deserializing checks the order pair by pair as they
+//! arrive instead of collecting and sorting them, which matters for tables with
+//! millions of knots. A pair out of order fails deserialization with its index rather
+//! than being sorted into place.
+//!
+//! Only linear tables can be written in this format.
+
+use std::fmt;
+
+use serde::de::{self, SeqAccess, Visitor};
+use serde::{ser, Deserializer, Serialize, Serializer};
+
+use crate::{checked_pair, FromIterError, InterpKind, Piecewise};
+
+pub fn serialize<S: Serializer>(table: &Piecewise, serializer: S) -> Result<S::Ok, S::Error> {
+    if table
+        .segment_kinds()
+        .iter()
+        .any(|&kind| kind != InterpKind::Linear)
+    {
+        return Err(ser::Error::custom(
+            "only linear tables can be written presorted",
+        ));
+    }
+    table.serialize(serializer)
+}
+
+pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Piecewise, D::Error> {
+    deserializer.deserialize_seq(PresortedVisitor)
+}
+
+struct PresortedVisitor;
+
+impl<'de> Visitor<'de> for PresortedVisitor {
+    type Value = Piecewise;
+
+    fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        formatter.write_str("an array of [x, y] pairs sorted by x")
+    }
+
+    fn visit_seq<A: SeqAccess<'de>>(self, mut seq: A) -> Result<Self::Value, A::Error> {
+        let pairs = std::iter::from_fn(|| seq.next_element::<(f64, f64)>().transpose())
+            .enumerate()
+            .map(|(index, pair)| {
+                let (x, y) = pair?;
+                checked_pair((x, y), true).map_err(|err| {
+                    de::Error::custom(format!("the knot at index {index} is invalid: {err}"))
+                })?;
+                Ok((x, y))
+            });
+        Piecewise::from_sorted_iter(pairs).map_err(|err| match err {
+            FromIterError::Source { error, .. } => error,
+            err => de::Error::custom(err),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde::de::IntoDeserializer;
+    use serde::{Deserialize, Serialize};
+
+    use crate::tests::SIDEARM;
+    use crate::{Coord, InterpKind, Piecewise};
+
+    #[derive(Debug, PartialEq, Serialize, Deserialize)]
+    struct Baked {
+        #[serde(with = "crate::presorted")]
+        curve: Piecewise,
+    }
+
+    #[test]
+    fn large_round_trip() {
+        let points = (0..200_000)
+            .map(|i| Coord::try_from((i as f64 * 0.5, (i as f64 * 0.01).sin())).unwrap())
+            .collect::<Vec<_>>();
+        let baked = Baked {
+            curve: Piecewise::try_from(points).unwrap(),
+        };
+        let json = serde_json::to_string(&baked).unwrap();
+        let back: Baked = serde_json::from_str(&json).unwrap();
+        assert_eq!(back, baked);
+        assert_eq!(
+            back.curve.y_at_x(1234.25).unwrap(),
+            baked.curve.y_at_x(1234.25).unwrap()
+        );
+    }
+
+    #[test]
+    fn rejects_unsorted() {
+        let unsorted = r#"{"curve": [[0, 18], [100, 42], [90, 36]]}"#;
+        let err = serde_json::from_str::<Baked>(unsorted).unwrap_err();
+        assert!(err.to_string().starts_with("Element 2 is invalid"), "{err}");
+        //the same input is fine through the sorting deserializer
+        let table: Piecewise = serde_json::from_str("[[0, 18], [100, 42], [90, 36]]").unwrap();
+        assert_eq!(table.as_slice(), SIDEARM);
+
+        let repeated = r#"{"curve": [[0, 18], [0, 18], [0, 19]]}"#;
+        assert!(serde_json::from_str::<Baked>(repeated).is_err());
+        assert!(serde_json::from_str::<Baked>(r#"{"curve": []}"#).is_err());
+        assert!(serde_json::from_str::<Baked>(r#"{"curve": [[0, "a"]]}"#).is_err());
+        let pairs = vec![vec![0.0, 18.0], vec![f64::INFINITY, 1.0]];
+        let err: serde::de::value::Error =
+            crate::presorted::deserialize(pairs.into_deserializer()).unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "the knot at index 1 is invalid: the x value inf is not finite"
+        );
+
+        let stepped = Baked {
+            curve: Piecewise::try_from(Vec::from(SIDEARM))
+                .unwrap()
+                .with_interp(InterpKind::StepPrevious)
+                .unwrap(),
+        };
+        assert!(serde_json::to_string(&stepped).is_err());
+    }
+}
@@ -0,0 +1,150 @@
+//! Python bindings, behind the `python` feature.
+
+use pyo3::exceptions::PyValueError;
+use pyo3::prelude::*;
+use pyo3::types::PyType;
+
+use crate::{Coord, Piecewise, PiecewiseErr};
+
+/// A [`Piecewise`] exposed to Python as `lerp_table.Piecewise`.
+///
+/// Every error is raised as a `ValueError` carrying the Rust error's message.
+#[pyclass(name = "Piecewise", module = "lerp_table", frozen)]
+#[derive(Debug, Clone, PartialEq)]
+pub struct PyPiecewise(Piecewise);
+
+#[pymethods]
+impl PyPiecewise {
+    #[new]
+    pub fn new(points: Vec<(f64, f64)>) -> PyResult<Self> {
+        let points = points
+            .into_iter()
+            .map(|point| Coord::try_from(point).map_err(PiecewiseErr::from))
+            .collect::<Result<Vec<_>, _>>()
+            .map_err(value_error)?;
+        Piecewise::try_from(points)
+            .map(PyPiecewise)
+            .map_err(value_error)
+    }
+
+    pub fn y_at_x(&self, x: f64) -> PyResult<f64> {
+        self.0.y_at_x(x).map_err(value_error)
+    }
+
+    pub fn __call__(&self, x: f64) -> PyResult<f64> {
+        self.y_at_x(x)
+    }
+
+    /// Evaluates every x of any sequence of floats, including numpy arrays.
+    pub fn y_at_xs(&self, xs: Vec<f64>) -> PyResult<Vec<f64>> {
+        self.0.y_at_xs(&xs).map_err(value_error)
+    }
+
+    #[getter]
+    pub fn points(&self) -> Vec<(f64, f64)> {
+        self.0
+            .as_slice()
+            .iter()
+            .map(|point| (point.x(), point.y()))
+            .collect()
+    }
+
+    pub fn to_json(&self) -> String {
+        serde_json::to_string(&self.0).expect("tables always serialize")
+    }
+
+    #[classmethod]
+    pub fn from_json(_cls: &Bound<'_, PyType>, json: &str) -> PyResult<Self> {
+        serde_json::from_str(json)
+            .map(PyPiecewise)
+            .map_err(value_error)
+    }
+
+    pub fn __repr__(&self) -> String {
+        format!("Piecewise({:?})", self.points())
+    }
+}
+
+impl From<Piecewise> for PyPiecewise {
+    fn from(value: Piecewise) -> Self {
+        PyPiecewise(value)
+    }
+}
+
+impl From<PyPiecewise> for Piecewise {
+    fn from(value: PyPiecewise) -> Self {
+        value.0
+    }
+}
+
+fn value_error(err: impl std::fmt::Display) -> PyErr {
+    PyValueError::new_err(err.to_string())
+}
+
+/// The `lerp_table` extension module.
+#[pymodule]
+fn lerp_table(m: &Bound<'_, PyModule>) -> PyResult<()> {
+    m.add_class::<PyPiecewise>()
+}
+
+#[cfg(test)]
+mod tests {
+    use pyo3::exceptions::PyValueError;
+    use pyo3::prelude::*;
+
+    use super::PyPiecewise;
+    use crate::tests::SIDEARM;
+    use crate::Piecewise;
+
+    fn sidearm() -> PyPiecewise {
+        PyPiecewise::new(vec![(90.0, 36.0), (0.0, 18.0), (100.0, 42.0)]).unwrap()
+    }
+
+    #[test]
+    fn glue() {
+        let table = sidearm();
+        assert_eq!(
+            Piecewise::from(table.clone()),
+            Piecewise::try_from(Vec::from(SIDEARM)).unwrap()
+        );
+        assert_eq!(table.points(), [(0.0, 18.0), (90.0, 36.0), (100.0, 42.0)]);
+        assert_eq!(table.y_at_xs(vec![0.0, 95.0]).unwrap(), [18.0, 39.0]);
+        assert_eq!(table.to_json(), "[[0.0,18.0],[90.0,36.0],[100.0,42.0]]");
+    }
+
+    #[test]
+    fn errors_are_value_errors() {
+        pyo3::prepare_freethreaded_python();
+        Python::with_gil(|py| {
+            let err = sidearm().y_at_x(101.0).unwrap_err();
+            assert!(err.is_instance_of::<PyValueError>(py));
+            assert_eq!(err.value(py).to_string(), "The value is not in the domain");
+
+            let err = PyPiecewise::new(vec![(0.0, 1.0), (0.0, 2.0)]).unwrap_err();
+            assert!(err.is_instance_of::<PyValueError>(py));
+            assert!(PyPiecewise::new(vec![(f64::NAN, 1.0)]).is_err());
+            assert!(sidearm().y_at_xs(vec![0.0, 200.0]).is_err());
+        });
+    }
+
+    #[test]
+    fn smoke() {
+        pyo3::prepare_freethreaded_python();
+        Python::with_gil(|py| {
+            let class = py.get_type::<PyPiecewise>();
+            let table = class
+                .call1((vec![(0.0, 18.0), (90.0, 36.0), (100.0, 42.0)],))
+                .unwrap();
+            let y: f64 = table.call1((95.0,)).unwrap().extract().unwrap();
+            assert_eq!(y, 39.0);
+
+            let json: String = table.call_method0("to_json").unwrap().extract().unwrap();
+            let back = class.call_method1("from_json", (json,)).unwrap();
+            let points: Vec<(f64, f64)> = back.getattr("points").unwrap().extract().unwrap();
+            assert_eq!(points.len(), 3);
+            assert!(class
+                .call_method1("from_json", ("[[0, 1], [0, 2]]",))
+                .is_err());
+        });
+    }
+}
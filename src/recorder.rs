@@ -0,0 +1,188 @@
+use crate::{Coord, Piecewise, PiecewiseErr};
+
+/// Collects `(x, y)` samples as they come in, in any order, and turns them into a
+/// table once recording is done.
+///
+/// Samples with a NaN or infinite value are dropped and counted, since a table built
+/// from them couldn't be evaluated anyway. Samples whose x are within `epsilon` of
+/// the first of a run of them are merged into one knot at their mean x and mean y, so
+/// repeated measurements at the same x average out instead of conflicting.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Recorder {
+    samples: Vec<(f64, f64)>,
+    epsilon: f64,
+    seen: usize,
+    nan_dropped: usize,
+}
+
+impl Recorder {
+    /// A recorder merging samples whose x are within `epsilon`. A negative or NaN
+    /// `epsilon` merges only samples with exactly the same x, like the default.
+    pub fn new(epsilon: f64) -> Recorder {
+        Recorder {
+            epsilon: epsilon.max(0.0),
+            ..Recorder::default()
+        }
+    }
+
+    pub fn observe(&mut self, x: f64, y: f64) {
+        self.seen += 1;
+        if !x.is_finite() || !y.is_finite() {
+            self.nan_dropped += 1;
+        } else {
+            self.samples.push((x, y));
+        }
+    }
+
+    /// How many samples were observed, including the dropped ones.
+    pub fn samples_seen(&self) -> usize {
+        self.seen
+    }
+
+    /// How many samples were dropped for holding a NaN or an infinite value.
+    pub fn nan_dropped(&self) -> usize {
+        self.nan_dropped
+    }
+
+    /// How many samples were merged into another one so far. This groups the samples
+    /// like [`Recorder::finish`] does, so it costs a sort.
+    pub fn collisions_merged(&self) -> usize {
+        self.samples.len() - self.merged().len()
+    }
+
+    /// Builds the table from the merged samples.
+    ///
+    /// If there are more of them than `max_knots`, the table is the least squares
+    /// [`Piecewise::fit`] of them with that many knots, so a budget below 2 errors with
+    /// [`PiecewiseErr::InvalidResolution`] when it can't be met. Nothing to build from
+    /// errors with [`PiecewiseErr::InputEmpty`].
+    pub fn finish(self, max_knots: Option<usize>) -> Result<Piecewise, PiecewiseErr> {
+        let merged = self.merged();
+        match max_knots {
+            Some(max_knots) if merged.len() > max_knots => Piecewise::fit(&merged, max_knots),
+            _ => merged
+                .into_iter()
+                .map(Coord::try_from)
+                .collect::<Result<Vec<_>, _>>()?
+                .try_into(),
+        }
+    }
+
+    /// The samples sorted by x, with each run of them within `epsilon` of its first
+    /// replaced by their mean.
+    fn merged(&self) -> Vec<(f64, f64)> {
+        let mut samples = self.samples.clone();
+        samples.sort_by(|a, b| a.0.total_cmp(&b.0));
+
+        let mut merged = Vec::new();
+        let mut rest = samples.as_slice();
+        while let Some(&(first, _)) = rest.first() {
+            let len = rest.partition_point(|&(x, _)| x == first || x - first <= self.epsilon);
+            let (run, after) = rest.split_at(len);
+            //a running mean, since summing first can overflow for values near the limits
+            let mut mean = (0.0, 0.0);
+            for (count, &(x, y)) in (1..).zip(run) {
+                let keep = (count - 1) as f64 / count as f64;
+                mean.0 = mean.0 * keep + x / count as f64;
+                mean.1 = mean.1 * keep + y / count as f64;
+            }
+            merged.push(mean);
+            rest = after;
+        }
+        merged
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Recorder;
+    use crate::PiecewiseErr;
+
+    #[test]
+    fn recovers_noisy_line() {
+        //a small LCG so the noise and the shuffle are repeatable
+        let mut state: u64 = 0x2545_f491_4f6c_dd1d;
+        let mut next = || {
+            state = state.wrapping_mul(6364136223846793005).wrapping_add(1);
+            (state >> 11) as f64 / (1u64 << 53) as f64
+        };
+        let line = |x: f64| 2.0 * x + 5.0;
+
+        //every x is measured four times
+        let mut samples = (0..2000)
+            .map(|i| {
+                let x = (i % 500) as f64 * 0.2;
+                (x, line(x) + next() - 0.5)
+            })
+            .collect::<Vec<_>>();
+        for i in (1..samples.len()).rev() {
+            samples.swap(i, (next() * (i + 1) as f64) as usize);
+        }
+
+        let mut recorder = Recorder::default();
+        for (i, &(x, y)) in samples.iter().enumerate() {
+            recorder.observe(x, y);
+            if i % 100 == 0 {
+                recorder.observe(f64::NAN, y);
+            }
+        }
+        assert_eq!(recorder.samples_seen(), 2020);
+        assert_eq!(recorder.nan_dropped(), 20);
+        assert_eq!(recorder.collisions_merged(), 1500);
+
+        let table = recorder.finish(Some(8)).unwrap();
+        assert!(table.points().len() <= 8);
+        assert_eq!(table.domain(), (0.0, 499.0 * 0.2));
+        for i in 0..=998 {
+            let x = i as f64 * 0.1;
+            let y = table.y_at_x(x).unwrap();
+            assert!((y - line(x)).abs() < 0.2, "{x}: {y}");
+        }
+    }
+
+    #[test]
+    fn merges_within_epsilon() {
+        let mut recorder = Recorder::new(0.01);
+        for (x, y) in [
+            (2.0, 5.0),
+            (1.0, 1.0),
+            (1.0078125, 3.0),
+            (1.02, 4.0),
+            (2.0, 7.0),
+        ] {
+            recorder.observe(x, y);
+        }
+        assert_eq!(recorder.collisions_merged(), 2);
+        let table = recorder.finish(None).unwrap();
+        assert_eq!(
+            table,
+            crate::tests::table(&[(1.00390625, 2.0), (1.02, 4.0), (2.0, 6.0)])
+        );
+
+        let mut recorder = Recorder::new(f64::NAN);
+        recorder.observe(1.0, 1.0);
+        recorder.observe(1.0 + f64::EPSILON, 2.0);
+        assert_eq!(recorder.collisions_merged(), 0);
+        assert_eq!(recorder.finish(Some(5)).unwrap().points().len(), 2);
+
+        let mut huge = Recorder::default();
+        for y in [f64::MAX, f64::MAX, f64::MAX / 2.0] {
+            huge.observe(1.0, y);
+        }
+        huge.observe(f64::INFINITY, 1.0);
+        huge.observe(2.0, f64::NEG_INFINITY);
+        assert_eq!(huge.nan_dropped(), 2);
+        assert_eq!(huge.collisions_merged(), 2);
+        let table = huge.finish(None).unwrap();
+        assert_eq!(table.points().len(), 1);
+        assert!(table.range().0.is_finite() && table.range().0 > f64::MAX / 2.0);
+
+        let mut empty = Recorder::default();
+        empty.observe(f64::NAN, f64::NAN);
+        assert!(matches!(empty.finish(None), Err(PiecewiseErr::InputEmpty)));
+        assert!(matches!(
+            Recorder::default().finish(Some(4)),
+            Err(PiecewiseErr::InputEmpty)
+        ));
+    }
+}
@@ -0,0 +1,87 @@
+//! Serialization rounded to a fixed number of decimal places.
+
+use serde::{ser, Serialize, Serializer};
+
+use crate::Piecewise;
+
+/// Serializes the wrapped table with every coordinate rounded to `DECIMALS` decimal
+/// places, so hand reviewed files don't fill up with values like
+/// `18.000000000000004`.
+///
+/// Only the output is rounded, the table itself is untouched, and the output reads
+/// back with the usual [`Piecewise`] deserializer. Knots rounded onto the same point
+/// are written once. Knots rounded onto the same x with different y can't be written
+/// and fail serialization rather than being nudged apart.
+#[derive(Debug, Clone, Copy)]
+pub struct RoundedPiecewise<'a, const DECIMALS: u8>(pub &'a Piecewise);
+
+impl<const DECIMALS: u8> Serialize for RoundedPiecewise<'_, DECIMALS> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let scale = 10f64.powi(DECIMALS.into());
+        let round = |value: f64| {
+            let scaled = (value * scale).round();
+            //values too large to have any decimals left are already exact
+            if scaled.is_finite() {
+                scaled / scale
+            } else {
+                value
+            }
+        };
+        self.0
+            .snapped_with(|point| (round(point.x()), round(point.y())))
+            .map_err(ser::Error::custom)?
+            .serialize(serializer)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::RoundedPiecewise;
+    use crate::tests::table;
+    use crate::{InterpKind, Piecewise, PiecewiseErr};
+
+    #[test]
+    fn rounded_output() {
+        let awkward = table(&[
+            (0.1 + 0.2, 18.000000000000004),
+            (1.0 / 3.0, -2.0 / 3.0),
+            (90.0, 36.0),
+            (1e300, 1.0005),
+        ]);
+        let json = serde_json::to_string(&RoundedPiecewise::<3>(&awkward)).unwrap();
+        assert_eq!(
+            json,
+            "[[0.3,18.0],[0.333,-0.667],[90.0,36.0],[1e300,1.001]]"
+        );
+        assert_eq!(awkward.as_slice()[0].x(), 0.1 + 0.2);
+
+        let back: Piecewise = serde_json::from_str(&json).unwrap();
+        assert_eq!(back.y_at_x(90.0).unwrap(), 36.0);
+
+        let stepped = awkward.with_interp(InterpKind::StepPrevious).unwrap();
+        let json = serde_json::to_string(&RoundedPiecewise::<2>(&stepped)).unwrap();
+        assert_eq!(
+            json,
+            r#"{"points":[[0.3,18.0],[0.33,-0.67],[90.0,36.0],[1e300,1.0]],"interp":"StepPrevious"}"#
+        );
+    }
+
+    #[test]
+    fn rounding_merges_or_fails() {
+        let close = table(&[(0.0, 1.0), (1.0001, 2.0), (0.9999, 2.0)]);
+        let json = serde_json::to_string(&RoundedPiecewise::<2>(&close)).unwrap();
+        assert_eq!(json, "[[0.0,1.0],[1.0,2.0]]");
+        serde_json::from_str::<Piecewise>(&json).unwrap();
+
+        let conflicting = table(&[(0.0, 1.0), (0.9999, 2.0), (1.0001, 3.0)]);
+        let err = serde_json::to_string(&RoundedPiecewise::<2>(&conflicting)).unwrap_err();
+        let expected = PiecewiseErr::InputUndefined {
+            x: 1.0,
+            y_first: 2.0,
+            y_second: 3.0,
+            sorted_index: 1,
+            original_indices: (1, 2),
+        };
+        assert_eq!(err.to_string(), expected.to_string());
+    }
+}
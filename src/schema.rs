@@ -0,0 +1,137 @@
+//! Hand-written `JsonSchema` impls describing the actual wire format, which the
+//! derive can't see through the custom serde impls.
+
+use schemars::gen::SchemaGenerator;
+use schemars::schema::{
+    ArrayValidation, InstanceType, Metadata, ObjectValidation, Schema, SchemaObject, SingleOrVec,
+    SubschemaValidation,
+};
+use schemars::JsonSchema;
+
+use crate::{Coord, InterpKind, Piecewise};
+
+fn number() -> Schema {
+    SchemaObject {
+        instance_type: Some(InstanceType::Number.into()),
+        ..Default::default()
+    }
+    .into()
+}
+
+fn described(description: &str) -> Option<Box<Metadata>> {
+    Some(Box::new(Metadata {
+        description: Some(description.to_owned()),
+        ..Default::default()
+    }))
+}
+
+impl JsonSchema for Coord {
+    fn schema_name() -> String {
+        "Coord".to_owned()
+    }
+
+    fn json_schema(_: &mut SchemaGenerator) -> Schema {
+        SchemaObject {
+            metadata: described("An [x, y] pair. Neither value may be NaN."),
+            instance_type: Some(InstanceType::Array.into()),
+            array: Some(Box::new(ArrayValidation {
+                items: Some(SingleOrVec::Vec(vec![number(), number()])),
+                min_items: Some(2),
+                max_items: Some(2),
+                ..Default::default()
+            })),
+            ..Default::default()
+        }
+        .into()
+    }
+}
+
+impl JsonSchema for Piecewise {
+    fn schema_name() -> String {
+        "Piecewise".to_owned()
+    }
+
+    fn json_schema(gen: &mut SchemaGenerator) -> Schema {
+        let sequence = SchemaObject {
+            metadata: described("Knots as [x, y] pairs, in any order."),
+            instance_type: Some(InstanceType::Array.into()),
+            array: Some(Box::new(ArrayValidation {
+                items: Some(SingleOrVec::Single(Box::new(gen.subschema_for::<Coord>()))),
+                min_items: Some(1),
+                ..Default::default()
+            })),
+            ..Default::default()
+        };
+        let map = SchemaObject {
+            metadata: described("Knots as a map of x, written as a numeric string, to y."),
+            instance_type: Some(InstanceType::Object.into()),
+            object: Some(Box::new(ObjectValidation {
+                min_properties: Some(1),
+                additional_properties: Some(Box::new(number())),
+                ..Default::default()
+            })),
+            ..Default::default()
+        };
+
+        let extended = SchemaObject {
+            metadata: described(
+                "Knots as [x, y] pairs along with how to interpolate them, either one interp for every segment or one of segments per sorted segment.",
+            ),
+            instance_type: Some(InstanceType::Object.into()),
+            object: Some(Box::new(ObjectValidation {
+                required: ["points".to_owned()].into(),
+                properties: [
+                    ("points".to_owned(), sequence.clone().into()),
+                    ("interp".to_owned(), gen.subschema_for::<InterpKind>()),
+                    (
+                        "segments".to_owned(),
+                        gen.subschema_for::<Vec<InterpKind>>(),
+                    ),
+                ]
+                .into(),
+                additional_properties: Some(Box::new(false.into())),
+                ..Default::default()
+            })),
+            ..Default::default()
+        };
+
+        SchemaObject {
+            metadata: described(
+                "A piecewise linear function. No value may be NaN and knots sharing an x must share a y.",
+            ),
+            subschemas: Some(Box::new(SubschemaValidation {
+                one_of: Some(vec![sequence.into(), map.into(), extended.into()]),
+                ..Default::default()
+            })),
+            ..Default::default()
+        }
+        .into()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::tests::SIDEARM;
+    use crate::Piecewise;
+
+    #[test]
+    fn serialized_output_matches_schema() {
+        let schema = serde_json::to_value(schemars::schema_for!(Piecewise)).unwrap();
+        let validator = jsonschema::validator_for(&schema).unwrap();
+
+        let table = Piecewise::try_from(Vec::from(SIDEARM)).unwrap();
+        assert!(validator.is_valid(&serde_json::to_value(&table).unwrap()));
+        assert!(validator.is_valid(&serde_json::json!({"0": 18, "90": 36})));
+        let log = table.with_interp(crate::InterpKind::LogY).unwrap();
+        assert!(validator.is_valid(&serde_json::to_value(&log).unwrap()));
+        let mut mixed = Piecewise::try_from(Vec::from(SIDEARM)).unwrap();
+        mixed
+            .set_segment_kind(1, crate::InterpKind::StepPrevious)
+            .unwrap();
+        assert!(validator.is_valid(&serde_json::to_value(&mixed).unwrap()));
+
+        assert!(!validator.is_valid(&serde_json::json!([])));
+        assert!(!validator.is_valid(&serde_json::json!([[0, 1, 2]])));
+        assert!(!validator.is_valid(&serde_json::json!({"0": "18"})));
+    }
+}
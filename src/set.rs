@@ -0,0 +1,148 @@
+use ordered_float::NotNan;
+
+use crate::{locate, Coord, Piecewise, PiecewiseErr, Position};
+
+/// Several tables that are always evaluated together at the same x.
+///
+/// When every table shares the exact same x knots this is detected on
+/// construction and evaluation does a single binary search for the whole set.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PiecewiseSet {
+    tables: Vec<Piecewise>,
+    shared_knots: bool,
+}
+
+impl TryFrom<Vec<Piecewise>> for PiecewiseSet {
+    type Error = PiecewiseErr;
+    fn try_from(tables: Vec<Piecewise>) -> Result<Self, Self::Error> {
+        let first = tables.first().ok_or(PiecewiseErr::InputEmpty)?.as_slice();
+        let shared_knots = tables.iter().all(|table| {
+            let data = table.as_slice();
+            data.len() == first.len() && data.iter().zip(first).all(|(a, b)| a.0 == b.0)
+        });
+
+        Ok(PiecewiseSet {
+            tables,
+            shared_knots,
+        })
+    }
+}
+
+impl PiecewiseSet {
+    /// Builds a set from columnar data, one y column per table, all sharing `xs`.
+    pub fn from_columns(xs: &[f64], columns: &[Vec<f64>]) -> Result<Self, PiecewiseErr> {
+        let mut tables = Vec::with_capacity(columns.len());
+        for ys in columns {
+            if ys.len() != xs.len() {
+                return Err(PiecewiseErr::LengthMismatch {
+                    expected: xs.len(),
+                    found: ys.len(),
+                });
+            }
+            let points = xs
+                .iter()
+                .zip(ys)
+                .map(|(&x, &y)| Coord::try_from((x, y)))
+                .collect::<Result<Vec<_>, _>>()?;
+            tables.push(Piecewise::try_from(points)?);
+        }
+        Self::try_from(tables)
+    }
+
+    pub fn len(&self) -> usize {
+        self.tables.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.tables.is_empty()
+    }
+
+    pub fn tables(&self) -> &[Piecewise] {
+        &self.tables
+    }
+
+    /// Whether every table has the same x knots, in which case one search serves all of them.
+    pub fn shares_knots(&self) -> bool {
+        self.shared_knots
+    }
+
+    pub fn eval_all(&self, x: f64) -> Result<Vec<f64>, PiecewiseErr> {
+        let mut buffer = vec![0.0; self.tables.len()];
+        self.eval_into(x, &mut buffer)?;
+        Ok(buffer)
+    }
+
+    /// Evaluates every table at `x`, writing the results in table order into `out`.
+    ///
+    /// `out` has to be exactly as long as the set.
+    pub fn eval_into(&self, x: f64, out: &mut [f64]) -> Result<(), PiecewiseErr> {
+        if out.len() != self.tables.len() {
+            return Err(PiecewiseErr::LengthMismatch {
+                expected: self.tables.len(),
+                found: out.len(),
+            });
+        }
+
+        if !self.shared_knots {
+            for (slot, table) in out.iter_mut().zip(&self.tables) {
+                *slot = table.y_at_x(x)?;
+            }
+            return Ok(());
+        }
+
+        let value = NotNan::new(x)?;
+        //every table has the same knots, so the first one can do the search for all of them
+        let position = locate(self.tables[0].as_slice(), value)?;
+        for (slot, table) in out.iter_mut().zip(&self.tables) {
+            let data = table.as_slice();
+            *slot = match position {
+                Position::Knot(i) => data[i].1.into_inner(),
+                Position::Between(i) => {
+                    table.1[i - 1].interpolate_finite(data[i - 1], data[i], value)?
+                }
+            };
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::PiecewiseSet;
+    use crate::tests::{table, SIDEARM};
+    use crate::Piecewise;
+
+    #[test]
+    fn matches_individual_evaluation() {
+        let tables = vec![
+            Piecewise::try_from(Vec::from(SIDEARM)).unwrap(),
+            table(&[(-10.0, 0.0), (50.0, 5.0), (200.0, 1.0)]),
+        ];
+        let set = PiecewiseSet::try_from(tables.clone()).unwrap();
+        assert!(!set.shares_knots());
+
+        for x in [0.0, 33.0, 90.0, 93.0, 100.0] {
+            let expected: Vec<f64> = tables.iter().map(|t| t.y_at_x(x).unwrap()).collect();
+            assert_eq!(set.eval_all(x).unwrap(), expected);
+        }
+        assert!(set.eval_all(150.0).is_err());
+    }
+
+    #[test]
+    fn shared_knots_fast_path() {
+        let xs = [0.0, 90.0, 100.0];
+        let set = PiecewiseSet::from_columns(&xs, &[vec![18.0, 36.0, 42.0], vec![1.0, 0.5, 0.0]])
+            .unwrap();
+        assert!(set.shares_knots());
+
+        let mut out = [0.0; 2];
+        for x in [0.0, 33.0, 90.0, 93.0, 100.0] {
+            set.eval_into(x, &mut out).unwrap();
+            for (value, table) in out.iter().zip(set.tables()) {
+                assert_eq!(*value, table.y_at_x(x).unwrap());
+            }
+        }
+        assert!(set.eval_into(101.0, &mut out).is_err());
+        assert!(set.eval_into(50.0, &mut [0.0; 3]).is_err());
+    }
+}
@@ -0,0 +1,52 @@
+//! [`proptest`] strategies for generating valid tables, behind the `proptest` feature.
+
+use std::ops::Range;
+
+use proptest::collection::vec;
+use proptest::prelude::*;
+
+use crate::{Coord, Piecewise};
+
+/// Generates tables with a knot count in `len_range` and knots drawn from `x_range`
+/// and `y_range`.
+///
+/// Knots that would repeat an x are dropped rather than failing the case, so a table
+/// can come out with fewer knots than drawn, but always at least one. Shrinking
+/// removes knots and moves values toward the start of their ranges.
+pub fn piecewise_strategy(
+    len_range: Range<usize>,
+    x_range: Range<f64>,
+    y_range: Range<f64>,
+) -> impl Strategy<Value = Piecewise> {
+    let len_range = len_range.start.max(1)..len_range.end.max(2);
+    vec((x_range, y_range), len_range).prop_map(|mut pairs| {
+        pairs.sort_by(|a, b| a.0.total_cmp(&b.0));
+        pairs.dedup_by(|a, b| a.0 == b.0);
+        let points = pairs
+            .into_iter()
+            .map(|pair| Coord::try_from(pair).expect("ranges only hold finite values"))
+            .collect::<Vec<_>>();
+        Piecewise::try_from(points).expect("x values were deduplicated")
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use proptest::prelude::*;
+
+    use super::piecewise_strategy;
+
+    proptest! {
+        #[test]
+        fn y_at_x_stays_in_range(
+            table in piecewise_strategy(1..20, -1e3..1e3, -1e3..1e3),
+            t in 0.0..=1.0f64,
+        ) {
+            let (min_x, max_x) = table.domain();
+            let x = (min_x + t * (max_x - min_x)).clamp(min_x, max_x);
+            let y = table.y_at_x(x).unwrap();
+            let (min_y, max_y) = table.range();
+            prop_assert!(min_y <= y && y <= max_y, "{} is outside {:?}", y, (min_y, max_y));
+        }
+    }
+}
@@ -0,0 +1,159 @@
+use ordered_float::NotNan;
+use serde::{de, Deserialize, Deserializer, Serialize, Serializer};
+
+use crate::{Coord, Piecewise, PiecewiseErr};
+
+/// A [`Piecewise`] whose knots each carry a piece of metadata.
+///
+/// The coordinates are sorted and validated exactly like a plain table, with each
+/// knot's metadata following it through the sort. Evaluation ignores the metadata.
+///
+/// Serializes as `[[x, y, metadata], ...]`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct PiecewiseTagged<M> {
+    table: Piecewise,
+    metadata: Vec<M>,
+}
+
+impl<M> TryFrom<Vec<(Coord, M)>> for PiecewiseTagged<M> {
+    type Error = PiecewiseErr;
+    fn try_from(knots: Vec<(Coord, M)>) -> Result<Self, Self::Error> {
+        //the sort in Piecewise::try_from is stable, so sorting here first keeps both in step
+        let mut knots = knots.into_iter().enumerate().collect::<Vec<_>>();
+        knots.sort_by_key(|(_, knot)| knot.0 .0);
+        let (order, knots): (Vec<usize>, Vec<(Coord, M)>) = knots.into_iter().unzip();
+        let (points, metadata): (Vec<Coord>, Vec<M>) = knots.into_iter().unzip();
+        let table = Piecewise::try_from(points).map_err(|err| match err {
+            //point the error back at the caller's order rather than ours
+            PiecewiseErr::InputUndefined {
+                x,
+                y_first,
+                y_second,
+                sorted_index,
+                original_indices: (i1, i2),
+            } => PiecewiseErr::InputUndefined {
+                x,
+                y_first,
+                y_second,
+                sorted_index,
+                original_indices: (order[i1], order[i2]),
+            },
+            err => err,
+        })?;
+        Ok(PiecewiseTagged { table, metadata })
+    }
+}
+
+impl<M> PiecewiseTagged<M> {
+    pub fn y_at_x(&self, value: f64) -> Result<f64, PiecewiseErr> {
+        self.table.y_at_x(value)
+    }
+
+    /// The untagged table.
+    pub fn table(&self) -> &Piecewise {
+        &self.table
+    }
+
+    /// The metadata of the knot at `index`, in sorted order.
+    pub fn metadata_at(&self, index: usize) -> Option<&M> {
+        self.metadata.get(index)
+    }
+
+    pub fn iter_with_metadata(&self) -> impl Iterator<Item = (&Coord, &M)> {
+        self.table.as_slice().iter().zip(&self.metadata)
+    }
+
+    pub fn map_metadata<N, F: FnMut(M) -> N>(self, f: F) -> PiecewiseTagged<N> {
+        PiecewiseTagged {
+            table: self.table,
+            metadata: self.metadata.into_iter().map(f).collect(),
+        }
+    }
+
+    /// Drops the metadata.
+    pub fn into_table(self) -> Piecewise {
+        self.table
+    }
+}
+
+impl<M: Serialize> Serialize for PiecewiseTagged<M> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_seq(
+            self.iter_with_metadata()
+                .map(|(point, metadata)| (point.0, point.1, metadata)),
+        )
+    }
+}
+
+impl<'de, M: Deserialize<'de>> Deserialize<'de> for PiecewiseTagged<M> {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let knots = Vec::<(NotNan<f64>, NotNan<f64>, M)>::deserialize(deserializer)?;
+        let knots = knots
+            .into_iter()
+            .map(|(x, y, metadata)| (Coord(x, y), metadata))
+            .collect::<Vec<_>>();
+        PiecewiseTagged::try_from(knots).map_err(de::Error::custom)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde::{Deserialize, Serialize};
+
+    use super::PiecewiseTagged;
+    use crate::Coord;
+
+    #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+    struct Authoring {
+        label: String,
+        locked: bool,
+    }
+
+    fn knot(x: f64, y: f64, label: &str) -> (Coord, Authoring) {
+        let metadata = Authoring {
+            label: label.to_owned(),
+            locked: x == 0.0,
+        };
+        (Coord::try_from((x, y)).unwrap(), metadata)
+    }
+
+    #[test]
+    fn sorting_keeps_metadata_attached() {
+        let tagged = PiecewiseTagged::try_from(vec![
+            knot(100.0, 42.0, "max"),
+            knot(0.0, 18.0, "start"),
+            knot(90.0, 36.0, "falloff"),
+        ])
+        .unwrap();
+
+        let labels: Vec<(f64, &str)> = tagged
+            .iter_with_metadata()
+            .map(|(point, metadata)| (point.x(), metadata.label.as_str()))
+            .collect();
+        assert_eq!(labels, [(0.0, "start"), (90.0, "falloff"), (100.0, "max")]);
+        assert!(tagged.metadata_at(0).unwrap().locked);
+        assert!(tagged.metadata_at(3).is_none());
+        assert_eq!(tagged.y_at_x(95.0).unwrap(), 39.0);
+
+        let lengths = tagged.map_metadata(|metadata| metadata.label.len());
+        assert_eq!(lengths.metadata_at(1), Some(&7));
+    }
+
+    #[test]
+    fn serde_round_trip() {
+        let tagged =
+            PiecewiseTagged::try_from(vec![knot(90.0, 36.0, "falloff"), knot(0.0, 18.0, "start")])
+                .unwrap();
+        let json = serde_json::to_string(&tagged).unwrap();
+        assert_eq!(
+            json,
+            r#"[[0.0,18.0,{"label":"start","locked":true}],[90.0,36.0,{"label":"falloff","locked":false}]]"#
+        );
+        let back: PiecewiseTagged<Authoring> = serde_json::from_str(&json).unwrap();
+        assert_eq!(back, tagged);
+
+        let conflict = r#"[[5, 0, "c"], [0, 1, "a"], [0, 2, "b"]]"#;
+        let err = serde_json::from_str::<PiecewiseTagged<String>>(conflict).unwrap_err();
+        assert!(err.to_string().contains("input indices 1 and 2"), "{err}");
+    }
+}
@@ -0,0 +1,117 @@
+//! JavaScript bindings, behind the `wasm` feature.
+
+use wasm_bindgen::prelude::*;
+
+use crate::Piecewise;
+
+/// A [`Piecewise`] exposed to JavaScript as `Piecewise`.
+///
+/// Errors are thrown as JavaScript `Error`s carrying the Rust error's message.
+#[wasm_bindgen(js_name = Piecewise)]
+#[derive(Debug, Clone, PartialEq)]
+pub struct JsPiecewise(Piecewise);
+
+#[wasm_bindgen(js_class = Piecewise)]
+impl JsPiecewise {
+    /// Parses any of the JSON forms the serde implementation accepts.
+    #[wasm_bindgen(js_name = fromJson)]
+    pub fn from_json(json: &str) -> Result<JsPiecewise, JsError> {
+        parse(json).map_err(js_error)
+    }
+
+    #[wasm_bindgen(js_name = toJson)]
+    pub fn to_json(&self) -> String {
+        serde_json::to_string(&self.0).expect("tables always serialize")
+    }
+
+    #[wasm_bindgen(js_name = yAtX)]
+    pub fn y_at_x(&self, x: f64) -> Result<f64, JsError> {
+        self.eval(x).map_err(js_error)
+    }
+
+    /// `[min_x, max_x]`.
+    pub fn domain(&self) -> Vec<f64> {
+        let (min, max) = self.0.domain();
+        vec![min, max]
+    }
+
+    /// The knots flattened as `[x0, y0, x1, y1, ...]`.
+    pub fn points(&self) -> Vec<f64> {
+        self.0
+            .as_slice()
+            .iter()
+            .flat_map(|point| [point.x(), point.y()])
+            .collect()
+    }
+}
+
+impl From<Piecewise> for JsPiecewise {
+    fn from(value: Piecewise) -> Self {
+        JsPiecewise(value)
+    }
+}
+
+impl From<JsPiecewise> for Piecewise {
+    fn from(value: JsPiecewise) -> Self {
+        value.0
+    }
+}
+
+//kept apart from the exported methods since JsError can only be built on wasm targets,
+//so the messages they throw can be tested natively
+fn parse(json: &str) -> Result<JsPiecewise, String> {
+    serde_json::from_str(json)
+        .map(JsPiecewise)
+        .map_err(|err| format!("invalid table: {err}"))
+}
+
+impl JsPiecewise {
+    fn eval(&self, x: f64) -> Result<f64, String> {
+        self.0.y_at_x(x).map_err(|err| err.to_string())
+    }
+}
+
+fn js_error(message: String) -> JsError {
+    JsError::new(&message)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{parse, JsPiecewise};
+    use crate::tests::SIDEARM;
+    use crate::{Piecewise, PiecewiseErr};
+
+    #[test]
+    fn wrapper_matches_table() {
+        let js = parse("[[0, 18], [90, 36], [100, 42]]").unwrap();
+        assert_eq!(
+            Piecewise::from(js.clone()),
+            Piecewise::try_from(Vec::from(SIDEARM)).unwrap()
+        );
+        assert_eq!(js.to_json(), "[[0.0,18.0],[90.0,36.0],[100.0,42.0]]");
+        assert_eq!(js.domain(), [0.0, 100.0]);
+        assert_eq!(js.points(), [0.0, 18.0, 90.0, 36.0, 100.0, 42.0]);
+        assert_eq!(js.0.y_at_x(95.0).unwrap(), 39.0);
+    }
+
+    #[test]
+    fn error_messages() {
+        let err = parse("[[0, 1], [0, 2]]").unwrap_err();
+        assert!(
+            err.starts_with("invalid table: The function is undefined at x = 0"),
+            "{err}"
+        );
+        assert!(parse("[[0, 1]").unwrap_err().starts_with("invalid table:"));
+
+        let js = JsPiecewise::from(Piecewise::try_from(Vec::from(SIDEARM)).unwrap());
+        assert_eq!(js.eval(95.0), Ok(39.0));
+        assert_eq!(
+            js.eval(101.0).unwrap_err(),
+            PiecewiseErr::NotInDomain.to_string()
+        );
+        assert_eq!(
+            js.eval(f64::NAN).unwrap_err(),
+            "NotNan constructed with NaN"
+        );
+    }
+}
@@ -0,0 +1,142 @@
+use ordered_float::NotNan;
+
+use crate::{locate, InterpKind, Piecewise, PiecewiseErr, Position};
+
+/// A [`Piecewise`] whose y values are angles, or anything else that wraps around
+/// modulo `period`.
+///
+/// Each segment turns the short way around from its left y to its right y, and
+/// results are normalized into `[0, period)`. Endpoints exactly half a period apart
+/// turn in the increasing direction. Construction and the domain are those of the
+/// inner table.
+#[derive(Debug, Clone, PartialEq)]
+pub struct WrappingPiecewise {
+    table: Piecewise,
+    period: f64,
+}
+
+impl WrappingPiecewise {
+    /// Errors if `period` isn't positive and finite, or if the table has log segments,
+    /// which have no meaning for angles. Step segments still hold the y of one knot.
+    pub fn new(table: Piecewise, period: f64) -> Result<WrappingPiecewise, PiecewiseErr> {
+        if !(period.is_finite() && period > 0.0) {
+            return Err(PiecewiseErr::InvalidPeriod(period));
+        }
+        if let Some(&kind) = table
+            .1
+            .iter()
+            .find(|&&kind| matches!(kind, InterpKind::LogY | InterpKind::LogLog))
+        {
+            return Err(PiecewiseErr::UnsupportedInterp(kind));
+        }
+        Ok(WrappingPiecewise { table, period })
+    }
+
+    pub fn y_at_x(&self, value: f64) -> Result<f64, PiecewiseErr> {
+        let value = NotNan::new(value)?;
+        let data = self.table.as_slice();
+
+        let index = match locate(data, value)? {
+            Position::Knot(x) => return Ok(self.normalize(data[x].y())),
+            Position::Between(x) => x,
+        };
+        let (left, right) = (data[index - 1], data[index]);
+        match self.table.1[index - 1] {
+            InterpKind::StepPrevious => return Ok(self.normalize(left.y())),
+            InterpKind::StepNext => return Ok(self.normalize(right.y())),
+            _ => (),
+        }
+
+        let mut turn = (right.y() - left.y()).rem_euclid(self.period);
+        if turn > self.period / 2.0 {
+            turn -= self.period;
+        }
+        let t = (value.into_inner() - left.x()) / (right.x() - left.x());
+        Ok(self.normalize(left.y() + t * turn))
+    }
+
+    fn normalize(&self, y: f64) -> f64 {
+        let y = y.rem_euclid(self.period);
+        //rem_euclid can round up to the period itself for tiny negative inputs
+        if y == self.period {
+            0.0
+        } else {
+            y
+        }
+    }
+
+    pub fn period(&self) -> f64 {
+        self.period
+    }
+
+    /// The table with its y values as given, unwrapped.
+    pub fn table(&self) -> &Piecewise {
+        &self.table
+    }
+
+    pub fn into_table(self) -> Piecewise {
+        self.table
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::WrappingPiecewise;
+    use crate::tests::table;
+    use crate::{InterpKind, PiecewiseErr};
+
+    fn degrees(points: &[(f64, f64)]) -> WrappingPiecewise {
+        WrappingPiecewise::new(table(points), 360.0).unwrap()
+    }
+
+    #[test]
+    fn crosses_zero() {
+        let up = degrees(&[(0.0, 350.0), (4.0, 10.0)]);
+        assert_eq!(up.y_at_x(1.0).unwrap(), 355.0);
+        assert_eq!(up.y_at_x(2.0).unwrap(), 0.0);
+        assert_eq!(up.y_at_x(3.0).unwrap(), 5.0);
+        assert_eq!(up.y_at_x(4.0).unwrap(), 10.0);
+
+        let down = degrees(&[(0.0, 10.0), (4.0, 350.0)]);
+        assert_eq!(down.y_at_x(1.0).unwrap(), 5.0);
+        assert_eq!(down.y_at_x(2.0).unwrap(), 0.0);
+        assert_eq!(down.y_at_x(3.0).unwrap(), 355.0);
+    }
+
+    #[test]
+    fn short_segments_match_lerp() {
+        let plain = table(&[(0.0, 40.0), (10.0, 90.0)]);
+        let wrapped = WrappingPiecewise::new(plain.clone(), 360.0).unwrap();
+        for x in [0.0, 2.5, 5.0, 9.0, 10.0] {
+            assert_eq!(wrapped.y_at_x(x).unwrap(), plain.y_at_x(x).unwrap());
+        }
+        assert!(wrapped.y_at_x(11.0).is_err());
+    }
+
+    #[test]
+    fn normalizes_and_breaks_ties() {
+        //half a turn apart goes the increasing way
+        let opposite = degrees(&[(0.0, 0.0), (2.0, 180.0)]);
+        assert_eq!(opposite.y_at_x(1.0).unwrap(), 90.0);
+        let opposite = degrees(&[(0.0, 180.0), (2.0, 0.0)]);
+        assert_eq!(opposite.y_at_x(1.0).unwrap(), 270.0);
+
+        let unnormalized = degrees(&[(0.0, -30.0), (1.0, 750.0)]);
+        assert_eq!(unnormalized.y_at_x(0.0).unwrap(), 330.0);
+        assert_eq!(unnormalized.y_at_x(1.0).unwrap(), 30.0);
+        assert_eq!(unnormalized.y_at_x(0.5).unwrap(), 0.0);
+    }
+
+    #[test]
+    fn rejects_bad_input() {
+        assert!(matches!(
+            WrappingPiecewise::new(table(&[(0.0, 1.0)]), 0.0),
+            Err(PiecewiseErr::InvalidPeriod(_))
+        ));
+        assert!(WrappingPiecewise::new(table(&[(0.0, 1.0)]), f64::INFINITY).is_err());
+        let log = table(&[(0.0, 1.0), (1.0, 2.0)])
+            .with_interp(InterpKind::LogY)
+            .unwrap();
+        assert!(WrappingPiecewise::new(log, 360.0).is_err());
+    }
+}